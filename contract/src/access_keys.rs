@@ -0,0 +1,47 @@
+use crate::*;
+use near_sdk::{Promise, PublicKey};
+
+// A function-call key on the contract account calls the contract as the contract itself, i.e. as the
+// admin, so a key for any of these methods would hand out full admin rights
+const FORBIDDEN_KEY_METHODS: [&str; 4] = ["grant_method_key", "revoke_method_key", "upgrade", "emergency_withdraw"];
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeyGrant {
+    pub methods: Vec<String>,
+    pub allowance: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Adds a key to the contract account that can only call `methods` of this contract, e.g. for
+    // partner automation. `allowance` is the gas budget of the key in yoctoNEAR.
+    pub fn grant_method_key(&mut self, public_key: PublicKey, methods: Vec<String>, allowance: U128) {
+        assert_admin();
+        assert!(!methods.is_empty(), "ERR_NO_METHODS");
+        assert!(allowance.0 > 0, "ERR_ZERO_ALLOWANCE");
+        for method in &methods {
+            assert!(!method.is_empty() && !method.contains(','), "ERR_INVALID_METHOD_NAME");
+            assert!(!FORBIDDEN_KEY_METHODS.contains(&method.as_str()), "ERR_ADMIN_METHOD");
+        }
+        assert!(self.key_grants.get(&public_key).is_none(), "ERR_KEY_ALREADY_GRANTED");
+
+        Promise::new(env::current_account_id()).add_access_key(
+            public_key.clone(),
+            allowance.0,
+            env::current_account_id(),
+            methods.join(","),
+        );
+        self.key_grants.insert(&public_key, &KeyGrant { methods, allowance });
+    }
+
+    pub fn revoke_method_key(&mut self, public_key: PublicKey) {
+        assert_admin();
+        self.key_grants.remove(&public_key).expect("ERR_UNKNOWN_KEY_GRANT");
+        Promise::new(env::current_account_id()).delete_key(public_key);
+    }
+
+    pub fn get_key_grants(&self, from_index: u64, limit: u64) -> Vec<(PublicKey, KeyGrant)> {
+        self.key_grants.iter().skip(from_index as usize).take(limit as usize).collect()
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+use near_sdk::Promise;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EmergencyWithdrawLog {
+    amount: U128,
+    to: AccountId,
+}
+
+// The contract account itself acts as the platform admin. There is no separate owner that could be
+// handed over, so no two-step (propose, then accept) ownership transfer either: changing the admin
+// means changing who controls the contract account's keys.
+pub(crate) fn assert_admin() {
+    assert_eq!(env::predecessor_account_id(), env::current_account_id(), "ERR_NOT_ADMIN");
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn verify_organizer(&mut self, organizer: AccountId) {
+        assert_admin();
+        self.verified_organizers.insert(&organizer);
+    }
+
+    pub fn unverify_organizer(&mut self, organizer: AccountId) {
+        assert_admin();
+        self.verified_organizers.remove(&organizer);
+    }
+
+    pub fn is_organizer_verified(&self, organizer: AccountId) -> bool {
+        self.verified_organizers.contains(&organizer)
+    }
+
+    // Last resort to rescue funds stranded on the contract. It bypasses all per-event accounting,
+    // only the balance locked for storage is kept. Guarded by the contract-account admin, see
+    // `assert_admin`.
+    pub fn emergency_withdraw(&mut self, amount: U128, to: AccountId) {
+        assert_admin();
+        let locked_for_storage = Balance::from(env::storage_usage()) * env::storage_byte_cost();
+        assert!(amount.0 <= env::account_balance().saturating_sub(locked_for_storage), "ERR_INSUFFICIENT_BALANCE");
+
+        emit_event("emergency_withdraw", EmergencyWithdrawLog { amount, to: to.clone() });
+        Promise::new(to).transfer(amount.0);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::*;
+use std::fmt;
+
+// Exact yoctoNEAR <-> NEAR string conversion without floats
+pub const NEAR_DECIMALS: u8 = 24;
+const YOCTO_PER_NEAR: u128 = 10u128.pow(NEAR_DECIMALS as u32);
+
+#[derive(Debug, PartialEq)]
+pub enum ContractError {
+    InvalidAmount,
+    AmountOverflow,
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::InvalidAmount => write!(f, "ERR_INVALID_AMOUNT"),
+            ContractError::AmountOverflow => write!(f, "ERR_AMOUNT_OVERFLOW"),
+        }
+    }
+}
+
+// Format yoctoNEAR as NEAR with at most `decimals` fractional digits. Extra digits are truncated,
+// never rounded up, and trailing zeros are trimmed: format_near(1_500_000_000_000_000_000_000_000, 2)
+// is "1.5".
+pub fn format_near(yocto: Balance, decimals: u8) -> String {
+    let decimals = decimals.min(NEAR_DECIMALS) as usize;
+    let whole = yocto / YOCTO_PER_NEAR;
+    let fraction = format!("{:024}", yocto % YOCTO_PER_NEAR);
+    let fraction = fraction[..decimals].trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+// Parse a NEAR amount like "1", "0.25" or "1.000000000000000000000001" into yoctoNEAR
+pub fn parse_near(s: &str) -> Result<Balance, ContractError> {
+    let (whole, fraction) = match s.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (s, ""),
+    };
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty()
+        || !is_digits(whole)
+        || !is_digits(fraction)
+        || (s.contains('.') && fraction.is_empty())
+        || fraction.len() > NEAR_DECIMALS as usize
+    {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    let whole: Balance = whole.parse().map_err(|_| ContractError::AmountOverflow)?;
+    let fraction: Balance = format!("{:0<24}", fraction).parse().unwrap();
+    whole
+        .checked_mul(YOCTO_PER_NEAR)
+        .and_then(|yocto| yocto.checked_add(fraction))
+        .ok_or(ContractError::AmountOverflow)
+}
+
+#[near_bindgen]
+impl Contract {
+    // number of fractional digits in the display strings of the caller's event
+    pub fn set_display_decimals(&mut self, decimals: u8) {
+        assert!(decimals <= NEAR_DECIMALS, "ERR_TOO_MANY_DECIMALS");
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.display_decimals = decimals;
+        self.internal_set_event(&event_owner_id, &mut event);
+    }
+}
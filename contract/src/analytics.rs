@@ -0,0 +1,38 @@
+use crate::*;
+
+// Opt-in metrics stream for indexers, a NEP-297 `metric` event per guest change:
+// `{"metric": "join" | "leave", "event_id": "<event owner>", "amount": "<event price>"}`.
+// The guest account is left out. Fields are only ever added to this schema, never renamed.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Metric {
+    Join,
+    Leave,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct MetricLog<'a> {
+    metric: Metric,
+    event_id: &'a EventOwnerId,
+    amount: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_analytics_enabled(&mut self, enabled: bool) {
+        assert_admin();
+        self.analytics_enabled = enabled;
+    }
+
+    pub fn is_analytics_enabled(&self) -> bool {
+        self.analytics_enabled
+    }
+
+    pub(crate) fn internal_emit_metric(&self, metric: Metric, event: &Event) {
+        if self.analytics_enabled {
+            emit_event("metric", MetricLog { metric, event_id: &event.owner_id, amount: U128::from(event.price) });
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+
+// Accounts can save events they're considering without joining. Every account has its own set of
+// bookmarked events, and every event counts its bookmarks as a popularity signal.
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn bookmark_event(&mut self, event_owner_id: EventOwnerId) {
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        let mut bookmarks = self.bookmarks.get(&account_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::Bookmarks { account_id: account_id.clone() })
+        });
+        assert!(bookmarks.insert(&event_owner_id), "ERR_ALREADY_BOOKMARKED");
+        self.bookmarks.insert(&account_id, &bookmarks);
+
+        event.bookmarks_count += 1;
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn remove_bookmark(&mut self, event_owner_id: EventOwnerId) {
+        let account_id = env::predecessor_account_id();
+        let mut bookmarks = self.bookmarks.get(&account_id).expect("ERR_NOT_BOOKMARKED");
+        assert!(bookmarks.remove(&event_owner_id), "ERR_NOT_BOOKMARKED");
+        if bookmarks.is_empty() {
+            self.bookmarks.remove(&account_id);
+        } else {
+            self.bookmarks.insert(&account_id, &bookmarks);
+        }
+
+        if let Some(mut event) = self.events.get(&event_owner_id) {
+            event.bookmarks_count -= 1;
+            self.internal_set_event(&event_owner_id, &mut event);
+        }
+    }
+
+    pub fn get_bookmarks(&self, account_id: AccountId, from_index: u64, limit: u64) -> Vec<EventOwnerId> {
+        match self.bookmarks.get(&account_id) {
+            Some(bookmarks) => bookmarks.iter().skip(from_index as usize).take(limit as usize).collect(),
+            None => vec![],
+        }
+    }
+}
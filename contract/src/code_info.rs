@@ -0,0 +1,57 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::Promise;
+
+// Build metadata embedded by build.rs, so auditors can match the deployed wasm to its source
+pub const BUILD_GIT_COMMIT: &str = env!("BUILD_GIT_COMMIT");
+pub const BUILD_RUSTC_VERSION: &str = env!("BUILD_RUSTC_VERSION");
+pub const BUILD_GIT_DIRTY: &str = env!("BUILD_GIT_DIRTY");
+
+// Layout version of the stored state. Bump it with every change to the stored structures and
+// convert the old layout in `migrate`, see migration.rs.
+pub const STATE_VERSION: u16 = 2;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct CodeInfoJSON {
+    pub commit: String,
+    pub rustc: String,
+    // built with uncommitted changes, so `commit` alone can't reproduce the wasm
+    pub dirty: bool,
+    // sha256 of the wasm deployed by the last `upgrade`, null on the first deploy
+    pub code_hash: Option<Base64VecU8>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_code_info(&self) -> CodeInfoJSON {
+        CodeInfoJSON {
+            commit: BUILD_GIT_COMMIT.to_string(),
+            rustc: BUILD_RUSTC_VERSION.to_string(),
+            dirty: BUILD_GIT_DIRTY == "true",
+            code_hash: self.code_hash.clone().map(Base64VecU8::from),
+        }
+    }
+
+    pub fn state_version(&self) -> u16 {
+        self.version
+    }
+}
+
+// Admin-only code upgrade. The new wasm is the raw call input rather than JSON, so this is a plain
+// exported function instead of a #[near_bindgen] method. The hash of the deployed code is recorded
+// before the deploy so `get_code_info` reports what is actually running.
+#[no_mangle]
+pub extern "C" fn upgrade() {
+    #[cfg(target_arch = "wasm32")]
+    env::setup_panic_hook();
+    assert_admin();
+    let code = env::input().expect("ERR_MISSING_CODE");
+
+    let mut contract: Contract = env::state_read().expect("ERR_CONTRACT_NOT_INITIALIZED");
+    contract.code_hash = Some(env::sha256(&code));
+    env::state_write(&contract);
+
+    Promise::new(env::current_account_id()).deploy_contract(code);
+}
@@ -0,0 +1,54 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+
+// Off-chain contact matching without raw PII on-chain. The organizer publishes a salt per event,
+// guests register `sha256(salt + email)` and the organizer matches exported hashes against their
+// own mailing list.
+const CONTACT_HASH_LENGTH: usize = 32;
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn set_contact_salt(&mut self, salt: Base64VecU8) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.contact_salt = Some(salt.into());
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_contact_salt(&self, event_owner_id: EventOwnerId) -> Option<Base64VecU8> {
+        self.internal_get_event(&event_owner_id).contact_salt.map(Base64VecU8::from)
+    }
+
+    // callable by a guest of the event
+    #[payable]
+    pub fn register_contact_hash(&mut self, event_owner_id: EventOwnerId, contact_hash: Base64VecU8) {
+        let initial_storage_usage = env::storage_usage();
+        let guest = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        assert!(event.contact_salt.is_some(), "ERR_CONTACT_SALT_NOT_SET");
+        assert!(event.guests.contains(&guest), "ERR_NOT_A_GUEST");
+        let contact_hash: [u8; CONTACT_HASH_LENGTH] = contact_hash.0
+            .try_into()
+            .unwrap_or_else(|_| panic!("ERR_INVALID_CONTACT_HASH_LENGTH"));
+
+        event.contact_hashes.insert(&guest, &contact_hash);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // Owner-only export. Views carry no caller, so this is a call method signed by the organizer.
+    // It only gates the method: like all contract state the hashes stay readable from raw storage.
+    pub fn export_contact_hashes(&mut self, event_owner_id: EventOwnerId, from_index: u64, limit: u64) -> Vec<(AccountId, Base64VecU8)> {
+        let event_owner_id = self.assert_event_owner(Some(event_owner_id));
+        let event = self.internal_get_event(&event_owner_id);
+        event.contact_hashes
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(guest, contact_hash)| (guest, Base64VecU8::from(contact_hash.to_vec())))
+            .collect()
+    }
+}
@@ -0,0 +1,56 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+
+// Long-form descriptions (e.g. markdown agendas) are uploaded in chunks under their own storage
+// keys, so the event struct stays small. Uploading a chunk takes the description offline until
+// `finalize_description` checks the sha256 of all chunks against the hash the organizer expects.
+// Clients can cache a description by that hash.
+const MAX_DESCRIPTION_CHUNKS: u32 = 16;
+const MAX_DESCRIPTION_CHUNK_LENGTH: usize = 4096;
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn set_description_chunk(&mut self, chunk_index: u32, data: String) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(chunk_index < MAX_DESCRIPTION_CHUNKS, "ERR_INVALID_CHUNK_INDEX");
+        assert!(data.len() <= MAX_DESCRIPTION_CHUNK_LENGTH, "ERR_DESCRIPTION_CHUNK_TOO_LONG");
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.description_chunks.insert(&chunk_index, &data);
+        event.description_hash = None;
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // chunks from `total_chunks` on are left over from a longer description and are removed
+    pub fn finalize_description(&mut self, total_chunks: u32, content_hash: Base64VecU8) {
+        assert!(total_chunks > 0 && total_chunks <= MAX_DESCRIPTION_CHUNKS, "ERR_INVALID_CHUNK_INDEX");
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        let content: String = (0..total_chunks)
+            .map(|chunk_index| event.description_chunks.get(&chunk_index).expect("ERR_MISSING_DESCRIPTION_CHUNK"))
+            .collect();
+        assert_eq!(env::sha256(content.as_bytes()), content_hash.0, "ERR_DESCRIPTION_HASH_MISMATCH");
+
+        for chunk_index in total_chunks..MAX_DESCRIPTION_CHUNKS {
+            event.description_chunks.remove(&chunk_index);
+        }
+        event.description_chunk_count = total_chunks;
+        event.description_hash = Some(content_hash.0);
+        self.internal_set_event(&event_owner_id, &mut event);
+    }
+
+    // None while the description is being replaced, and for private events unless `viewer` is the
+    // owner or a guest, `viewer` isn't verified
+    pub fn get_description(&self, event_owner_id: EventOwnerId, chunk_index: u32, viewer: Option<AccountId>) -> Option<String> {
+        let event = self.internal_get_event(&event_owner_id);
+        if !event.is_visible_to(viewer.as_ref())
+            || event.description_hash.is_none()
+            || chunk_index >= event.description_chunk_count {
+            return None;
+        }
+        event.description_chunks.get(&chunk_index)
+    }
+}
@@ -1,7 +1,138 @@
 use crate::*;
+use crate::guests::GuestProvenance;
+use crate::localization::EventLocalization;
+use crate::milestones::EventMilestone;
+use crate::promo::PromoCode;
+use crate::registration_log::LogEntry;
+use crate::signup::SignupField;
+use crate::visibility::Visibility;
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Event {
+    pub owner_id: EventOwnerId,
     pub price: u128,
     pub guests: UnorderedSet<AccountId>,
-}
\ No newline at end of file
+    pub slug: Option<String>,
+    pub milestones: Vec<EventMilestone>,
+    // accounts a relayer may register on the owner's behalf, see relay.rs
+    pub preauthorized: LookupSet<AccountId>,
+    // salted contact hashes registered by guests, see contacts.rs
+    pub contact_salt: Option<Vec<u8>>,
+    pub contact_hashes: UnorderedMap<AccountId, [u8; 32]>,
+    // app-specific key-value data, see extensions.rs
+    pub extensions: UnorderedMap<String, String>,
+    // block timestamp of the last write, see modifications.rs
+    pub last_modified_at: u64,
+    // hashes of one-time invite secrets, see invites.rs
+    pub invites: UnorderedSet<Vec<u8>>,
+    pub redeemed_invites: LookupSet<Vec<u8>>,
+    // localized title and description by language code, see localization.rs
+    pub localizations: UnorderedMap<String, EventLocalization>,
+    // fractional digits of display amounts, see amounts.rs
+    pub display_decimals: u8,
+    // codes granting free access, see promo.rs
+    pub promo_codes: UnorderedMap<String, PromoCode>,
+    // codes with uses remaining
+    pub active_promo_codes: u32,
+    // number of accounts that bookmarked the event, see bookmarks.rs
+    pub bookmarks_count: u64,
+    // sequential ticket number of every guest, never reused after a guest is removed
+    pub ticket_numbers: LookupMap<AccountId, u64>,
+    // who added every guest and how, see guests.rs
+    pub guest_provenance: LookupMap<AccountId, GuestProvenance>,
+    pub last_ticket_number: u64,
+    // guests must belong to one of these account domains, no restriction if empty
+    pub allowed_account_domains: Vec<String>,
+    // drawn once, see raffle.rs
+    pub raffle_winners: Option<Vec<AccountId>>,
+    // form guests fill in to join and their answers, see signup.rs
+    pub signup_form_fields: Vec<SignupField>,
+    pub signup_responses: UnorderedMap<AccountId, Vec<(String, String)>>,
+    // seats left to every allocation partner, see reservations.rs
+    pub reserved_blocks: UnorderedMap<AccountId, u32>,
+    // joins and departures in order, see registration_log.rs
+    pub registration_log: Vector<LogEntry>,
+    // who can find the event, see visibility.rs
+    pub visibility: Visibility,
+    // the price can't be changed anymore, see lock.rs
+    pub settings_locked: bool,
+    // chunked long-form description, live when the hash is set, see description.rs
+    pub description_chunks: LookupMap<u32, String>,
+    pub description_chunk_count: u32,
+    pub description_hash: Option<Vec<u8>>,
+}
+
+impl Event {
+    // Every guests list has its own UnorderedSet structure initialized by a unique key of
+    // BorshStorageKey, derived from the event owner
+    pub fn new(event_owner_id: &EventOwnerId, price: Balance) -> Self {
+        Self {
+            owner_id: event_owner_id.clone(),
+            price,
+            guests: UnorderedSet::new(StorageKey::Guests {
+                event_owner_id: event_owner_id.clone()
+            }),
+            slug: None,
+            milestones: vec![],
+            preauthorized: LookupSet::new(StorageKey::Preauthorized {
+                event_owner_id: event_owner_id.clone()
+            }),
+            contact_salt: None,
+            contact_hashes: UnorderedMap::new(StorageKey::ContactHashes {
+                event_owner_id: event_owner_id.clone()
+            }),
+            extensions: UnorderedMap::new(StorageKey::Extensions {
+                event_owner_id: event_owner_id.clone()
+            }),
+            last_modified_at: 0,
+            invites: UnorderedSet::new(StorageKey::Invites {
+                event_owner_id: event_owner_id.clone()
+            }),
+            redeemed_invites: LookupSet::new(StorageKey::RedeemedInvites {
+                event_owner_id: event_owner_id.clone()
+            }),
+            localizations: UnorderedMap::new(StorageKey::Localizations {
+                event_owner_id: event_owner_id.clone()
+            }),
+            display_decimals: DEFAULT_DISPLAY_DECIMALS,
+            promo_codes: UnorderedMap::new(StorageKey::PromoCodes {
+                event_owner_id: event_owner_id.clone()
+            }),
+            active_promo_codes: 0,
+            bookmarks_count: 0,
+            ticket_numbers: LookupMap::new(StorageKey::TicketNumbers {
+                event_owner_id: event_owner_id.clone()
+            }),
+            last_ticket_number: 0,
+            guest_provenance: LookupMap::new(StorageKey::GuestProvenance {
+                event_owner_id: event_owner_id.clone()
+            }),
+            allowed_account_domains: vec![],
+            raffle_winners: None,
+            signup_form_fields: vec![],
+            signup_responses: UnorderedMap::new(StorageKey::SignupResponses {
+                event_owner_id: event_owner_id.clone()
+            }),
+            reserved_blocks: UnorderedMap::new(StorageKey::ReservedBlocks {
+                event_owner_id: event_owner_id.clone()
+            }),
+            registration_log: Vector::new(StorageKey::RegistrationLog {
+                event_owner_id: event_owner_id.clone()
+            }),
+            visibility: Visibility::Public,
+            settings_locked: false,
+            description_chunks: LookupMap::new(StorageKey::DescriptionChunks {
+                event_owner_id: event_owner_id.clone()
+            }),
+            description_chunk_count: 0,
+            description_hash: None,
+        }
+    }
+
+    // e.g. `alice.university.edu` belongs to the `university.edu` domain
+    pub fn is_domain_allowed(&self, guest: &AccountId) -> bool {
+        let guest = guest.as_str().to_lowercase();
+        self.allowed_account_domains.is_empty()
+            || self.allowed_account_domains.iter().any(|domain| guest.ends_with(&format!(".{}", domain)))
+    }
+}
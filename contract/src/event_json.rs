@@ -1,18 +1,121 @@
 use crate::*;
+use crate::amounts::format_near;
+use near_sdk::json_types::Base64VecU8;
+use crate::extensions::{MAX_EXTENSIONS, MAX_EXTENSIONS_TOTAL_LENGTH, MAX_EXTENSION_KEY_LENGTH, MAX_EXTENSION_VALUE_LENGTH};
+use crate::visibility::Visibility;
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize)]
+// Input of `insert_event` and output of the views. Output-only fields can't be deserialized and
+// unknown keys are rejected, so `insert_event` fails on them instead of ignoring them.
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
+// JavaScript clients expect camelCase keys
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct EventJSON {
     pub price: U128,
-    pub guests: Vec<AccountId>
+    pub guests: Vec<AccountId>,
+    // rendered as a JSON object, optional on input
+    #[serde(default)]
+    pub extensions: BTreeMap<String, String>,
+    // output only, computed from the contract's verified organizers
+    #[serde(skip_deserializing)]
+    pub organizer_verified: bool,
+    // output only, the price in NEAR formatted with the event's display decimals
+    #[serde(skip_deserializing)]
+    pub price_display: String,
+    // output only
+    #[serde(skip_deserializing)]
+    pub bookmarks_count: u64,
+    // output only, change it with `set_visibility`. Private hides nothing from a determined reader,
+    // see visibility.rs
+    #[serde(skip_deserializing, default = "default_visibility")]
+    pub visibility: Visibility,
+    // output only, change it with `lock_settings`
+    #[serde(skip_deserializing)]
+    pub settings_locked: bool,
+    // output only, sha256 of the live description, see `get_description`
+    #[serde(skip_deserializing)]
+    pub description_hash: Option<Base64VecU8>,
 }
 
-// method to create EventJSON on a fly
-impl From<Event> for EventJSON {
-    fn from(event: Event) -> Self {
+fn default_visibility() -> Visibility {
+    Visibility::Public
+}
+
+impl Event {
+    // method to create EventJSON on a fly, some fields are computed from the contract state
+    pub fn to_json(&self, contract: &Contract) -> EventJSON {
         EventJSON {
-            price: U128::from(event.price),
-            guests: event.guests.to_vec()
+            price: U128::from(self.price),
+            guests: self.guests.to_vec(),
+            extensions: self.extensions.iter().collect(),
+            organizer_verified: contract.verified_organizers.contains(&self.owner_id),
+            price_display: format_near(self.price, self.display_decimals),
+            bookmarks_count: self.bookmarks_count,
+            visibility: self.visibility,
+            settings_locked: self.settings_locked,
+            description_hash: self.description_hash.clone().map(Base64VecU8::from),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &str, message: &str) -> Self {
+        Self { field: field.to_string(), message: message.to_string() }
+    }
+}
+
+// Checks an EventJSON before it is submitted to `insert_event`, for tests and off-chain tooling.
+// Extension keys and values are trimmed and duplicate guests are dropped (first occurrence wins).
+// Guest account ids aren't normalized: an AccountId is validated whenever it's parsed or
+// deserialized, and valid ids are already lowercase without whitespace, so there is nothing left
+// to normalize. Every problem is reported rather than only the first one.
+pub fn validate_and_sanitize_event_json(raw: EventJSON) -> Result<EventJSON, Vec<ValidationError>> {
+    let mut errors = vec![];
+
+    if raw.price.0 == 0 {
+        errors.push(ValidationError::new("price", "ERR_ZERO_PRICE"));
+    }
+
+    let mut guests: Vec<AccountId> = Vec::with_capacity(raw.guests.len());
+    for guest in raw.guests {
+        if !guests.contains(&guest) {
+            guests.push(guest);
+        }
+    }
+
+    let mut extensions = BTreeMap::new();
+    for (key, value) in raw.extensions {
+        let (key, value) = (key.trim().to_string(), value.trim().to_string());
+        let field = format!("extensions.{}", key);
+        if key.is_empty() || key.len() > MAX_EXTENSION_KEY_LENGTH {
+            errors.push(ValidationError::new(&field, "ERR_EXTENSION_KEY_TOO_LONG"));
         }
+        if value.len() > MAX_EXTENSION_VALUE_LENGTH {
+            errors.push(ValidationError::new(&field, "ERR_EXTENSION_VALUE_TOO_LONG"));
+        }
+        if extensions.insert(key, value).is_some() {
+            errors.push(ValidationError::new(&field, "ERR_DUPLICATE_EXTENSION_KEY"));
+        }
+    }
+    if extensions.len() as u64 > MAX_EXTENSIONS {
+        errors.push(ValidationError::new("extensions", "ERR_TOO_MANY_EXTENSIONS"));
+    }
+    let total_length: usize = extensions.iter().map(|(key, value)| key.len() + value.len()).sum();
+    if total_length > MAX_EXTENSIONS_TOTAL_LENGTH {
+        errors.push(ValidationError::new("extensions", "ERR_EXTENSIONS_TOO_LARGE"));
+    }
+
+    if errors.is_empty() {
+        Ok(EventJSON { guests, extensions, ..raw })
+    } else {
+        Err(errors)
     }
 }
@@ -0,0 +1,54 @@
+use crate::*;
+
+// Small key-value bag for app-specific data attached by third-party frontends, e.g.
+// `"app.discord.channel"`. Keys are namespaced by convention only.
+pub(crate) const MAX_EXTENSIONS: u64 = 16;
+pub(crate) const MAX_EXTENSION_KEY_LENGTH: usize = 32;
+pub(crate) const MAX_EXTENSION_VALUE_LENGTH: usize = 256;
+// total bytes of all keys and values of an event
+pub(crate) const MAX_EXTENSIONS_TOTAL_LENGTH: usize = 2048;
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn set_extension(&mut self, key: String, value: String) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        internal_set_extension(&mut event, key, value);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn remove_extension(&mut self, key: String) {
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.extensions.remove(&key).expect("ERR_MISSING_EXTENSION");
+        self.internal_set_event(&event_owner_id, &mut event);
+    }
+
+    // None for private events unless `viewer` is the owner or a guest, `viewer` isn't verified
+    pub fn get_extension(&self, event_owner_id: EventOwnerId, key: String, viewer: Option<AccountId>) -> Option<String> {
+        let event = self.internal_get_event(&event_owner_id);
+        if !event.is_visible_to(viewer.as_ref()) {
+            return None;
+        }
+        event.extensions.get(&key)
+    }
+}
+
+// set extension helper, overwrites the value of an existing key
+pub(crate) fn internal_set_extension(event: &mut Event, key: String, value: String) {
+    assert!(!key.is_empty() && key.len() <= MAX_EXTENSION_KEY_LENGTH, "ERR_EXTENSION_KEY_TOO_LONG");
+    assert!(value.len() <= MAX_EXTENSION_VALUE_LENGTH, "ERR_EXTENSION_VALUE_TOO_LONG");
+
+    let total_length: usize = event.extensions
+        .iter()
+        .filter(|(existing_key, _)| existing_key != &key)
+        .map(|(existing_key, existing_value)| existing_key.len() + existing_value.len())
+        .sum();
+    assert!(total_length + key.len() + value.len() <= MAX_EXTENSIONS_TOTAL_LENGTH, "ERR_EXTENSIONS_TOO_LARGE");
+
+    let is_new_key = event.extensions.insert(&key, &value).is_none();
+    assert!(!is_new_key || event.extensions.len() <= MAX_EXTENSIONS, "ERR_TOO_MANY_EXTENSIONS");
+}
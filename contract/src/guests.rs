@@ -0,0 +1,282 @@
+use crate::*;
+use near_sdk::json_types::U64;
+
+// entries accepted by a single `set_guests_lenient` call
+const MAX_LENIENT_BATCH: usize = 500;
+const MAX_ALLOWED_DOMAINS: usize = 5;
+// same as the longest account id
+const MAX_DOMAIN_LENGTH: usize = 64;
+// accounts accepted by a single `diff_guests` call
+const MAX_DIFF_INPUT: usize = 1000;
+
+// Pattern for bulk guest removal. Every field that is set must match for a guest to be removed,
+// e.g. `{"suffix": ".spam.near"}` removes every guest whose account ends with `.spam.near`.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GuestPatternJSON {
+    pub suffix: Option<String>,
+    pub prefix: Option<String>,
+    pub exact: Option<Vec<AccountId>>,
+}
+
+impl GuestPatternJSON {
+    fn matches(&self, guest: &AccountId) -> bool {
+        self.suffix.as_ref().is_none_or(|suffix| guest.as_str().ends_with(suffix.as_str()))
+            && self.prefix.as_ref().is_none_or(|prefix| guest.as_str().starts_with(prefix.as_str()))
+            && self.exact.as_ref().is_none_or(|exact| exact.contains(guest))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum GuestStatus {
+    Added,
+    Duplicate,
+    Invalid,
+    DomainNotAllowed,
+}
+
+impl GuestStatus {
+    // panic message of the strict path
+    pub(crate) fn error_code(&self) -> &'static str {
+        match self {
+            GuestStatus::Added => "",
+            GuestStatus::Duplicate => "ERR_ALREADY_A_GUEST",
+            GuestStatus::Invalid => "ERR_INVALID_ACCOUNT_ID",
+            GuestStatus::DomainNotAllowed => "ERR_DOMAIN_NOT_ALLOWED",
+        }
+    }
+}
+
+// How a guest was added to the event
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum JoinMethod {
+    OwnerSet,
+    Relayed,
+    InviteCode,
+    PromoCode,
+    SignupForm,
+    Reserved,
+}
+
+// Who added a guest and when, settles disputes about how someone joined. Only the latest addition
+// is kept.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct GuestProvenance {
+    pub added_by: AccountId,
+    // nanoseconds, a string in JSON so JavaScript clients don't lose precision
+    pub added_at: U64,
+    pub via: JoinMethod,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct GuestSweepJSON {
+    pub removed: u64,
+    // pass it as `from_index` to continue the sweep, null when done
+    pub next_index: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct GuestDiff {
+    pub only_on_chain: Vec<AccountId>,
+    pub only_external: Vec<AccountId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct GuestResultJSON {
+    pub account_id: String,
+    pub status: GuestStatus,
+}
+
+// Rules for a new guest, shared by the strict and the lenient paths so they can't diverge
+pub(crate) fn check_guest(event: &Event, guest: &str) -> Result<AccountId, GuestStatus> {
+    let guest: AccountId = guest.parse().map_err(|_| GuestStatus::Invalid)?;
+    if event.guests.contains(&guest) {
+        return Err(GuestStatus::Duplicate);
+    }
+    if !event.is_domain_allowed(&guest) {
+        return Err(GuestStatus::DomainNotAllowed);
+    }
+    Ok(guest)
+}
+
+#[near_bindgen]
+impl Contract {
+    // Adds guests for CSV-style imports: every entry is checked on its own and gets a status, and
+    // all valid entries are added. Only problems with the whole call (missing event, batch too
+    // large) panic.
+    #[payable]
+    pub fn set_guests_lenient(&mut self, guests: Vec<String>) -> Vec<GuestResultJSON> {
+        let initial_storage_usage = env::storage_usage();
+        assert!(guests.len() <= MAX_LENIENT_BATCH, "ERR_BATCH_TOO_LARGE");
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        let results = guests.into_iter()
+            .map(|account_id| {
+                let status = match check_guest(&event, &account_id) {
+                    Ok(guest) => {
+                        self.internal_add_guest(&event_owner_id, &mut event, &guest, JoinMethod::OwnerSet);
+                        GuestStatus::Added
+                    }
+                    Err(status) => status,
+                };
+                GuestResultJSON { account_id, status }
+            })
+            .collect();
+
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+        results
+    }
+
+    // Remove guests matching the pattern. Scans at most `limit` guests starting at `from_index`
+    // and returns the index where the next call continues, `None` once the end of the list is
+    // reached. A call can remove nothing and still have more guests to scan. Every removal appends
+    // to the registration log, so the caller pays for that storage.
+    #[payable]
+    pub fn remove_guests_matching(
+        &mut self,
+        event_owner_id_or_self: Option<EventOwnerId>,
+        pattern: GuestPatternJSON,
+        from_index: u64,
+        limit: u64,
+    ) -> GuestSweepJSON {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = self.assert_event_owner(event_owner_id_or_self);
+        assert!(
+            pattern.suffix.is_some() || pattern.prefix.is_some() || pattern.exact.is_some(),
+            "ERR_EMPTY_PATTERN"
+        );
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        let mut index = from_index;
+        let mut scanned = 0;
+        let mut removed = 0;
+        while scanned < limit && index < event.guests.len() {
+            let guest = event.guests.as_vector().get(index).unwrap();
+            scanned += 1;
+            if pattern.matches(&guest) {
+                // the last guest is swapped into `index`, so it is checked next without moving on
+                self.internal_remove_guest(&mut event, &guest);
+                removed += 1;
+            } else {
+                index += 1;
+            }
+        }
+
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+        GuestSweepJSON {
+            removed,
+            next_index: if index < event.guests.len() { Some(index) } else { None },
+        }
+    }
+
+    // Number of distinct accounts that have ever joined any event. Removing a guest doesn't
+    // decrement it: the account may still be a guest elsewhere, and tracking that would need a
+    // per-account event counter.
+    pub fn total_unique_guests(&self) -> u64 {
+        self.unique_guests.len()
+    }
+
+    // Reconciles the guest list with an off-chain source of truth
+    pub fn diff_guests(&self, event_owner_id: EventOwnerId, external: Vec<AccountId>) -> GuestDiff {
+        assert!(external.len() <= MAX_DIFF_INPUT, "ERR_BATCH_TOO_LARGE");
+        let event = self.internal_get_event(&event_owner_id);
+        let external: std::collections::HashSet<AccountId> = external.into_iter().collect();
+        let mut only_external: Vec<AccountId> = external.iter()
+            .filter(|guest| !event.guests.contains(guest))
+            .cloned()
+            .collect();
+        only_external.sort();
+        GuestDiff {
+            only_on_chain: event.guests.iter().filter(|guest| !external.contains(guest)).collect(),
+            only_external,
+        }
+    }
+
+    pub fn get_guest_provenance(&self, event_owner_id: EventOwnerId, guest: AccountId) -> Option<GuestProvenance> {
+        self.internal_get_event(&event_owner_id).guest_provenance.get(&guest)
+    }
+
+    // Restricts guests to accounts of the given domains, e.g. `["university.edu"]`. An empty list
+    // lifts the restriction. Current guests are not affected.
+    #[payable]
+    pub fn set_domain_restrictions(&mut self, domains: Vec<String>) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        assert!(domains.len() <= MAX_ALLOWED_DOMAINS, "ERR_TOO_MANY_DOMAINS");
+        event.allowed_account_domains = domains.iter()
+            .map(|domain| domain.trim().trim_start_matches('.').to_lowercase())
+            .collect();
+        assert!(
+            event.allowed_account_domains.iter().all(|domain| !domain.is_empty() && domain.len() <= MAX_DOMAIN_LENGTH),
+            "ERR_INVALID_DOMAIN"
+        );
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // Guests are numbered 1, 2, ... in the order they joined, e.g. for numbered seats or raffles
+    pub fn ticket_number(&self, event_owner_id: EventOwnerId, guest: AccountId) -> Option<u64> {
+        self.internal_get_event(&event_owner_id).ticket_numbers.get(&guest)
+    }
+
+    // add guest helper, keep all per-guest data in sync here
+    pub(crate) fn internal_add_guest(&mut self, event_owner_id: &EventOwnerId, event: &mut Event, guest: &AccountId, via: JoinMethod) -> bool {
+        assert!(event.is_domain_allowed(guest), "{}", GuestStatus::DomainNotAllowed.error_code());
+        self.unique_guests.insert(guest);
+        let added = event.guests.insert(guest);
+        if added {
+            event.last_ticket_number += 1;
+            event.ticket_numbers.insert(guest, &event.last_ticket_number);
+            event.guest_provenance.insert(guest, &GuestProvenance {
+                added_by: env::predecessor_account_id(),
+                added_at: U64::from(env::block_timestamp()),
+                via,
+            });
+            self.internal_trigger_webhooks(event_owner_id, WebhookEventType::GuestAdded, json!({
+                "event_owner_id": event_owner_id,
+                "guest": guest,
+            }));
+            internal_log_registration(event, guest, RegistrationAction::Joined);
+            self.internal_emit_metric(Metric::Join, event);
+        }
+        added
+    }
+
+    // remove guest helper, keep all per-guest data in sync here
+    pub(crate) fn internal_remove_guest(&mut self, event: &mut Event, guest: &AccountId) -> bool {
+        event.contact_hashes.remove(guest);
+        event.ticket_numbers.remove(guest);
+        event.guest_provenance.remove(guest);
+        event.signup_responses.remove(guest);
+        let removed = event.guests.remove(guest);
+        if removed {
+            internal_log_registration(event, guest, RegistrationAction::Left);
+            self.internal_emit_metric(Metric::Leave, event);
+        }
+        removed
+    }
+
+    // only the event owner can manage the event, `None` stands for the caller's own event
+    pub(crate) fn assert_event_owner(&self, event_owner_id: Option<EventOwnerId>) -> EventOwnerId {
+        let predecessor = env::predecessor_account_id();
+        let event_owner_id = event_owner_id.unwrap_or_else(|| predecessor.clone());
+        assert_eq!(event_owner_id, predecessor, "ERR_NOT_EVENT_OWNER");
+        event_owner_id
+    }
+}
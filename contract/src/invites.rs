@@ -0,0 +1,66 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+
+// Single-use invitations. The organizer generates random secrets off-chain and submits only their
+// sha256 hashes, the secrets go to the guests through private channels. Transaction results are
+// public on-chain, so secrets returned by the contract could be scraped and redeemed by anyone.
+// Redeeming a secret consumes its hash, so a forwarded link can only be used by whoever redeems it
+// first.
+const MAX_INVITES_PER_CALL: usize = 50;
+const MAX_PENDING_INVITES: u64 = 500;
+const INVITE_HASH_LENGTH: usize = 32;
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn create_one_time_invites(&mut self, invite_hashes: Vec<Base64VecU8>) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(!invite_hashes.is_empty() && invite_hashes.len() <= MAX_INVITES_PER_CALL, "ERR_INVALID_INVITES_COUNT");
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        for invite_hash in invite_hashes {
+            assert_eq!(invite_hash.0.len(), INVITE_HASH_LENGTH, "ERR_INVALID_INVITE_HASH");
+            assert!(!event.redeemed_invites.contains(&invite_hash.0), "ERR_INVITE_ALREADY_USED");
+            event.invites.insert(&invite_hash.0);
+        }
+        assert!(event.invites.len() <= MAX_PENDING_INVITES, "ERR_TOO_MANY_INVITES");
+
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // joins the caller to the event
+    #[payable]
+    pub fn redeem_invite(&mut self, event_owner_id: EventOwnerId, secret: Base64VecU8) {
+        let initial_storage_usage = env::storage_usage();
+        let mut event = self.internal_get_event(&event_owner_id);
+        // checked first, so the invite isn't burned by an account that can't join
+        let guest = match check_guest(&event, env::predecessor_account_id().as_str()) {
+            Ok(guest) => guest,
+            Err(status) => panic!("{}", status.error_code()),
+        };
+        let invite_hash = env::sha256(&secret.0);
+        assert!(!event.redeemed_invites.contains(&invite_hash), "ERR_INVITE_ALREADY_USED");
+        assert!(event.invites.remove(&invite_hash), "ERR_INVALID_INVITE");
+        event.redeemed_invites.insert(&invite_hash);
+
+        self.internal_add_guest(&event_owner_id, &mut event, &guest, JoinMethod::InviteCode);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // revoke all unredeemed invites of the caller's event, returns how many were revoked
+    pub fn revoke_invites(&mut self) -> u64 {
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        let revoked = event.invites.len();
+        event.invites.clear();
+        self.internal_set_event(&event_owner_id, &mut event);
+        revoked
+    }
+
+    pub fn pending_invites_count(&self, event_owner_id: EventOwnerId) -> u64 {
+        self.internal_get_event(&event_owner_id).invites.len()
+    }
+}
@@ -0,0 +1,41 @@
+use crate::*;
+use crate::visibility::Visibility;
+
+// Payload of an old frontend: the guests are named `guest_list` and the price is a bare number.
+// Output always uses the modern EventJSON shape.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LegacyEventJSON {
+    pub price: Balance,
+    pub guest_list: Vec<AccountId>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct DeprecatedPayloadLog {
+    method: &'static str,
+    account_id: AccountId,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Deprecated, use `insert_event`. The log lets us measure when it's safe to remove.
+    #[payable]
+    pub fn insert_event_legacy(&mut self, payload: LegacyEventJSON) {
+        emit_event("deprecated_payload", DeprecatedPayloadLog {
+            method: "insert_event_legacy",
+            account_id: env::predecessor_account_id(),
+        });
+        self.insert_event(EventJSON {
+            price: U128::from(payload.price),
+            guests: payload.guest_list,
+            extensions: Default::default(),
+            organizer_verified: false,
+            price_display: String::new(),
+            bookmarks_count: 0,
+            visibility: Visibility::Public,
+            settings_locked: false,
+            description_hash: None,
+        });
+    }
+}
@@ -23,9 +23,10 @@ use near_sdk::borsh::{self, BorshSerialize, BorshDeserialize};
 // Import JSON (default) serialization from near_sdk::serde
 use near_sdk::serde::{Serialize, Deserialize};
 
-use near_sdk::{AccountId, BorshStorageKey, env, near_bindgen};
-use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::{AccountId, Balance, BorshStorageKey, PublicKey, env, near_bindgen};
+use near_sdk::collections::{LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
 
 // Define the contract structure
 // We read/write data about events, each event belongs to corresponding NEAR account and contains:
@@ -36,13 +37,52 @@ use near_sdk::json_types::U128;
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Contract {
-    events: LookupMap<EventOwnerId, Event>
+    events: UnorderedMap<EventOwnerId, Event>,
+    // human-readable event identifiers, see slug.rs
+    slugs: UnorderedMap<String, EventOwnerId>,
+    // every account that has ever been a guest of any event, see guests.rs
+    unique_guests: UnorderedSet<AccountId>,
+    // organizers verified by the admin, see admin.rs
+    verified_organizers: UnorderedSet<AccountId>,
+    // webhook endpoints per organizer, see webhooks.rs
+    webhooks: UnorderedMap<AccountId, Vec<WebhookRegistration>>,
+    // sha256 of the deployed wasm, see code_info.rs
+    code_hash: Option<Vec<u8>>,
+    // layout version of this state, see code_info.rs. Every layout since version 1 starts with the
+    // same fields up to this one, so `migrate` can read it, new fields go below.
+    version: u16,
+    // event owners by the timestamp of their last modification, see modifications.rs
+    events_by_modification_time: TreeMap<u64, UnorderedSet<EventOwnerId>>,
+    // events bookmarked by every account, see bookmarks.rs
+    bookmarks: LookupMap<AccountId, UnorderedSet<EventOwnerId>>,
+    // block height of the contract initialization, see network.rs
+    created_at_block: u64,
+    // function-call keys added to the contract account, see access_keys.rs
+    key_grants: UnorderedMap<PublicKey, KeyGrant>,
+    // events with promo codes that can still be redeemed, see promo.rs
+    events_with_active_promotions: UnorderedSet<EventOwnerId>,
+    // metric logs for indexers, off by default, see analytics.rs
+    analytics_enabled: bool,
 }
 
 // Define the default, which automatically initializes the contract
 impl Default for Contract{
     fn default() -> Self{
-        Self{events: LookupMap::new(StorageKey::Events)}
+        Self{
+            events: UnorderedMap::new(StorageKey::Events),
+            slugs: UnorderedMap::new(StorageKey::Slugs),
+            unique_guests: UnorderedSet::new(StorageKey::UniqueGuests),
+            verified_organizers: UnorderedSet::new(StorageKey::VerifiedOrganizers),
+            webhooks: UnorderedMap::new(StorageKey::Webhooks),
+            code_hash: None,
+            version: STATE_VERSION,
+            events_by_modification_time: TreeMap::new(StorageKey::EventsByModificationTime),
+            bookmarks: LookupMap::new(StorageKey::AccountBookmarks),
+            created_at_block: env::block_height(),
+            key_grants: UnorderedMap::new(StorageKey::KeyGrants),
+            events_with_active_promotions: UnorderedSet::new(StorageKey::EventsWithActivePromotions),
+            analytics_enabled: false,
+        }
     }
 }
 
@@ -64,7 +104,7 @@ impl Contract {
     // In order to mitigate this issue lets create another object EventJSON to properly support
     // JSON output, check event.json.rs file
 
-    // this method works because we converted Event => EventJSON on a fly (event.json.rs#11).
+    // this method works because we converted Event => EventJSON on a fly (Event::to_json in event_json.rs).
     // We converted Balance => WrappedBalance and UnorderedSet => Vec, to store data in the most
     // efficient and optimized way and output it in a JavaScript friendly format
 
@@ -72,7 +112,7 @@ impl Contract {
     pub fn get_event(&self, event_owner_id: EventOwnerId) -> EventJSON {
         self
             .internal_get_event(&event_owner_id)// Get Event
-            .into() // Convert to EventJSON
+            .to_visible_json(self) // Convert to EventJSON, private events are reduced to a stub
     }
 
     // ================= 2 ==================
@@ -91,16 +131,43 @@ impl Contract {
     // If we provide event as EventJSON, we can parse it and create an Event object. Every guests
     // list has its own UnorderedSet structure initialized by a unique key of BorshStorageKey
 
+    // Writing data costs storage, so the caller attaches a deposit to cover it (storage.rs)
+
     //LEGIT
+    #[payable]
     pub fn insert_event(&mut self, event: EventJSON) {
+        let initial_storage_usage = env::storage_usage();
         let event_owner_id = env::predecessor_account_id();
-        self.events.insert(&event_owner_id.clone(), &Event {
-            price: event.price.0,
-            guests: UnorderedSet::new(StorageKey::Guests{
-                event_owner_id
-            })
-        });
-        self.set_guests(event.guests);
+        // A previously inserted event is updated in place: a new Event would reuse the storage
+        // prefixes of its collections and leave their old entries behind. Only the price and the
+        // guests change, guests missing from the new list are removed like any other guest.
+        let mut new_event = match self.events.get(&event_owner_id) {
+            Some(mut old_event) => {
+                assert!(!old_event.settings_locked, "ERR_SETTINGS_LOCKED");
+                old_event.price = event.price.0;
+                let kept_guests: std::collections::HashSet<&AccountId> = event.guests.iter().collect();
+                let dropped_guests: Vec<AccountId> = old_event.guests
+                    .iter()
+                    .filter(|guest| !kept_guests.contains(guest))
+                    .collect();
+                for guest in dropped_guests {
+                    self.internal_remove_guest(&mut old_event, &guest);
+                }
+                old_event
+            }
+            None => {
+                self.internal_trigger_webhooks(&event_owner_id, WebhookEventType::EventCreated, json!({
+                    "event_owner_id": event_owner_id,
+                }));
+                Event::new(&event_owner_id, event.price.0)
+            }
+        };
+        for (key, value) in event.extensions {
+            internal_set_extension(&mut new_event, key, value);
+        }
+        self.internal_set_event(&event_owner_id, &mut new_event);
+        self.internal_set_guests(&event_owner_id, event.guests);
+        refund_deposit(initial_storage_usage);
     }
 
     // helper method to set a list of guests. Again, we can't create a public method and provide
@@ -115,18 +182,35 @@ impl Contract {
      */
 
     // We can provide a Vec and fill the UnorderedSet object instead
+    #[payable]
     pub fn set_guests(&mut self, guests: Vec<AccountId>) {
-        let mut event = self.internal_get_event(&env::predecessor_account_id());
+        let initial_storage_usage = env::storage_usage();
+        self.internal_set_guests(&env::predecessor_account_id(), guests);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // set guests helper
+    pub(crate) fn internal_set_guests(&mut self, event_owner_id: &EventOwnerId, guests: Vec<AccountId>) {
+        let mut event = self.internal_get_event(event_owner_id);
         for guest in guests {
-            event.guests.insert(&guest);
+            // duplicates are skipped, any other rejection fails the whole call
+            match check_guest(&event, guest.as_str()) {
+                Ok(guest) => {
+                    self.internal_add_guest(event_owner_id, &mut event, &guest, JoinMethod::OwnerSet);
+                }
+                Err(GuestStatus::Duplicate) => {}
+                Err(status) => panic!("{}", status.error_code()),
+            }
         }
-        self.internal_set_event(&env::predecessor_account_id(), &event);
+        self.internal_set_event(event_owner_id, &mut event);
     }
 
     // And ew can easily use any Borsh object as a parameter in a private method, like this setter:
 
-    // set event helper
-    pub(crate) fn internal_set_event(&mut self, event_owner_id: &EventOwnerId, event: &Event) {
+    // set event helper, every write goes through here to keep the indexes up to date
+    pub(crate) fn internal_set_event(&mut self, event_owner_id: &EventOwnerId, event: &mut Event) {
+        self.internal_touch_event(event_owner_id, event);
+        self.internal_index_promotions(event_owner_id, event);
         self.events.insert(event_owner_id, event);
     }
 
@@ -145,31 +229,139 @@ impl Contract {
 #[derive(BorshSerialize, BorshStorageKey)]
 pub enum StorageKey {
     Events,
-    Guests {event_owner_id: EventOwnerId}
+    Guests {event_owner_id: EventOwnerId},
+    Slugs,
+    UniqueGuests,
+    Preauthorized {event_owner_id: EventOwnerId},
+    ContactHashes {event_owner_id: EventOwnerId},
+    Extensions {event_owner_id: EventOwnerId},
+    EventsByModificationTime,
+    ModifiedEvents {timestamp: u64},
+    Webhooks,
+    Invites {event_owner_id: EventOwnerId},
+    RedeemedInvites {event_owner_id: EventOwnerId},
+    Localizations {event_owner_id: EventOwnerId},
+    VerifiedOrganizers,
+    PromoCodes {event_owner_id: EventOwnerId},
+    AccountBookmarks,
+    Bookmarks {account_id: AccountId},
+    KeyGrants,
+    TicketNumbers {event_owner_id: EventOwnerId},
+    GuestProvenance {event_owner_id: EventOwnerId},
+    SignupResponses {event_owner_id: EventOwnerId},
+    ReservedBlocks {event_owner_id: EventOwnerId},
+    RegistrationLog {event_owner_id: EventOwnerId},
+    EventsWithActivePromotions,
+    DescriptionChunks {event_owner_id: EventOwnerId},
 }
 
+mod access_keys;
+mod admin;
+mod analytics;
+pub mod amounts;
+mod bookmarks;
+mod code_info;
+mod contacts;
+mod description;
 mod event;
 mod event_json;
+mod extensions;
+mod guests;
+mod invites;
+mod legacy;
+mod localization;
+mod lock;
+mod logs;
+mod migration;
+mod milestones;
+mod modifications;
+mod network;
+mod permissions;
+mod promo;
+mod raffle;
+mod registration_log;
+mod relay;
+mod reservations;
+mod signup;
+mod slug;
+mod stats;
+mod storage;
+mod visibility;
+mod webhooks;
 use event::*;
-use event_json::*;
+pub use event_json::{EventJSON, ValidationError, validate_and_sanitize_event_json};
+use access_keys::KeyGrant;
+use admin::assert_admin;
+use analytics::Metric;
+use code_info::STATE_VERSION;
+use extensions::internal_set_extension;
+use guests::{GuestStatus, JoinMethod, check_guest};
+use logs::emit_event;
+use registration_log::{RegistrationAction, internal_log_registration};
+use storage::refund_deposit;
+use webhooks::{WebhookEventType, WebhookRegistration};
 
 type EventOwnerId = AccountId;
 
+const DEFAULT_DISPLAY_DECIMALS: u8 = 2;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::amounts::{ContractError, format_near, parse_near};
+    use crate::guests::{GuestPatternJSON, GuestStatus, GuestSweepJSON, JoinMethod};
+    use crate::localization::EventLocalization;
+    use crate::permissions::ViewerRole;
+    use crate::signup::{FieldType, SignupField};
+    use crate::visibility::Visibility;
+    use near_sdk::json_types::{Base64VecU8, U64};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    mod snapshots;
+
+    fn account(name: &str) -> AccountId {
+        AccountId::new_unchecked(name.to_string())
+    }
+
+    // enough to cover the storage of any test event
+    const STORAGE_DEPOSIT: Balance = 1_000_000_000_000_000_000_000_000;
+
+    fn event_json(price: Balance, guests: Vec<AccountId>) -> EventJSON {
+        EventJSON {
+            price: U128::from(price),
+            guests,
+            extensions: Default::default(),
+            organizer_verified: false,
+            price_display: String::new(),
+            bookmarks_count: 0,
+            visibility: Visibility::Public,
+            settings_locked: false,
+            description_hash: None,
+        }
+    }
+
+    fn set_predecessor(account_id: &AccountId) {
+        set_context(account_id, 0);
+    }
+
+    fn set_context(account_id: &AccountId, block_timestamp: u64) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.clone())
+            .block_timestamp(block_timestamp)
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+    }
 
     #[test]
     fn test_event() {
         let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
 
-        contract.insert_event(EventJSON {
-            price: WrappedBalance::from(1000000000000000000000000),
-            guests: vec!(
-                AccountId::new_unchecked("alice.testnet".to_string()),
-                AccountId::new_unchecked("bob.testnet".to_string())
-            )
-        });
+        contract.insert_event(event_json(1000000000000000000000000, vec!(
+            AccountId::new_unchecked("alice.testnet".to_string()),
+            AccountId::new_unchecked("bob.testnet".to_string())
+        )));
 
         let event = contract.get_event(env::predecessor_account_id());
 
@@ -177,4 +369,1993 @@ mod tests {
         assert_eq!(event.guests.len(), 2);
         assert_eq!(event.guests[0].to_string(), "alice.testnet".to_string());
     }
+
+    #[test]
+    fn test_reinsert_event_keeps_event_data() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let (bob, carol, dave, erin) = (account("bob.testnet"), account("carol.testnet"), account("dave.testnet"), account("erin.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+        contract.set_slug("party".to_string());
+        contract.add_event_milestone("book venue".to_string(), U64::from(100));
+        contract.preauthorize(alice.clone(), erin.clone());
+        contract.set_contact_salt(Base64VecU8::from(b"salt".to_vec()));
+        contract.set_extension("app.discord.channel".to_string(), "123".to_string());
+        contract.create_one_time_invites(invite_hashes(&[b"one", b"two"]));
+        contract.set_event_localization("en".to_string(), EventLocalization {
+            title: "Party".to_string(),
+            description: "Welcome".to_string(),
+        });
+        contract.create_promo_code("VIP".to_string(), None, 1);
+        contract.set_signup_form(signup_form());
+        contract.reserve_block(alice.clone(), account("sponsor.testnet"), 2);
+        contract.set_description_chunk(0, "agenda".to_string());
+        contract.finalize_description(1, Base64VecU8::from(env::sha256(b"agenda")));
+        set_predecessor(&carol);
+        contract.register_contact_hash(alice.clone(), Base64VecU8::from(vec![1; 32]));
+        contract.bookmark_event(alice.clone());
+        set_predecessor(&dave);
+        contract.submit_signup_form(alice.clone(), vec!(
+            ("name".to_string(), "Dave".to_string()),
+            ("email".to_string(), signup_contact_hash("dave@example.com")),
+        ));
+
+        // bob and dave are dropped, carol stays
+        set_predecessor(&alice);
+        contract.insert_event(event_json(20, vec!(carol.clone())));
+
+        let event = contract.get_event(alice.clone());
+        assert_eq!((event.price.0, event.guests), (20, vec!(carol.clone())));
+        assert_eq!(event.bookmarks_count, 1);
+        assert!(event.description_hash.is_some());
+        assert_eq!(contract.get_event_by_slug("party".to_string()).unwrap().price.0, 20);
+        assert_eq!(contract.get_events_with_upcoming_milestones(alice.clone(), 1).len(), 1);
+        assert_eq!(contract.export_contact_hashes(alice.clone(), 0, 10).len(), 1);
+        assert_eq!(contract.get_extension(alice.clone(), "app.discord.channel".to_string(), None), Some("123".to_string()));
+        assert_eq!(contract.pending_invites_count(alice.clone()), 2);
+        assert_eq!(contract.get_available_languages(alice.clone()), vec!("en".to_string()));
+        assert_eq!(contract.get_events_with_active_promotions(0, 10).len(), 1);
+        assert_eq!(contract.get_signup_form(alice.clone()).len(), 3);
+        assert_eq!(contract.reserved_remaining(alice.clone(), account("sponsor.testnet")), 2);
+        assert_eq!(contract.get_description(alice.clone(), 0, None), Some("agenda".to_string()));
+        assert_eq!(contract.ticket_number(alice.clone(), carol.clone()), Some(2));
+        assert!(contract.get_guest_provenance(alice.clone(), carol.clone()).is_some());
+
+        // dropped guests are cleaned up like removed ones
+        for guest in [&bob, &dave] {
+            assert_eq!(contract.ticket_number(alice.clone(), guest.clone()), None);
+            assert!(contract.get_guest_provenance(alice.clone(), guest.clone()).is_none());
+        }
+        assert!(contract.get_signup_response(alice.clone(), dave.clone()).is_none());
+        assert_eq!(contract.registration_log(alice.clone(), 0, 10).len(), 5);
+
+        set_predecessor(&account("relayer.testnet"));
+        contract.relayed_join(alice.clone(), erin.clone());
+        assert_eq!(contract.get_event(alice).guests, vec!(carol, erin));
+    }
+
+    #[test]
+    fn test_event_json_keys() {
+        let event = event_json(1, vec!(AccountId::new_unchecked("alice.testnet".to_string())));
+
+        let json = near_sdk::serde_json::to_value(&event).unwrap();
+        let mut keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        keys.sort();
+
+        assert_eq!(keys, vec!["bookmarksCount", "descriptionHash", "extensions", "guests", "organizerVerified", "price", "priceDisplay", "settingsLocked", "visibility"]);
+    }
+
+    #[test]
+    fn test_event_json_input() {
+        let event: EventJSON = near_sdk::serde_json::from_str(r#"{"price":"10","guests":["bob.testnet"]}"#).unwrap();
+        assert_eq!((event.price.0, event.guests.len(), event.visibility), (10, 1, Visibility::Public));
+
+        // output-only fields are rejected rather than silently ignored
+        for field in ["organizerVerified", "priceDisplay", "bookmarksCount", "visibility", "settingsLocked", "descriptionHash"] {
+            let raw = format!(r#"{{"price":"10","guests":[],"{}":null}}"#, field);
+            let error = near_sdk::serde_json::from_str::<EventJSON>(&raw).unwrap_err();
+            assert!(error.to_string().starts_with(&format!("unknown field `{}`", field)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_slug() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        contract.set_slug("My-Cool-Event".to_string());
+
+        let event = contract.get_event_by_slug("my-cool-event".to_string()).unwrap();
+        assert_eq!(event.price.0, 10);
+        assert!(contract.get_event_by_slug("MY-COOL-EVENT".to_string()).is_some());
+        assert!(contract.get_event_by_slug("unknown".to_string()).is_none());
+
+        // replacing the slug releases the old one
+        contract.set_slug("renamed".to_string());
+        assert!(contract.get_event_by_slug("my-cool-event".to_string()).is_none());
+        assert!(contract.get_event_by_slug("renamed".to_string()).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_SLUG_TAKEN")]
+    fn test_slug_duplicate() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_slug("party".to_string());
+
+        set_predecessor(&account("bob.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_slug("Party".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SLUG")]
+    fn test_slug_not_url_safe() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_slug("my event?".to_string());
+    }
+
+    #[test]
+    fn test_remove_guests_matching() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!(
+            account("bob.testnet"),
+            account("a.spam.near"),
+            account("carol.testnet"),
+            account("b.spam.near"),
+            account("dave.testnet"),
+            account("c.spam.near"),
+            account("erin.testnet"),
+        )));
+        let pattern = || GuestPatternJSON { suffix: Some(".spam.near".to_string()), prefix: None, exact: None };
+
+        // paginated sweep over 7 guests, 2 at a time, until the end of the list
+        let mut removed = 0;
+        let mut from_index = Some(0);
+        while let Some(index) = from_index {
+            let sweep = contract.remove_guests_matching(None, pattern(), index, 2);
+            removed += sweep.removed;
+            from_index = sweep.next_index;
+        }
+        assert_eq!(removed, 3);
+        assert_eq!(contract.remove_guests_matching(None, pattern(), 0, 10), GuestSweepJSON { removed: 0, next_index: None });
+
+        let mut guests: Vec<String> = contract.get_event(account("alice.testnet"))
+            .guests.iter().map(|guest| guest.to_string()).collect();
+        guests.sort();
+        assert_eq!(guests, vec!["bob.testnet", "carol.testnet", "dave.testnet", "erin.testnet"]);
+    }
+
+    #[test]
+    fn test_remove_guests_matching_window_without_matches() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"), account("carol.testnet"), account("a.spam.near"))));
+        let pattern = || GuestPatternJSON { suffix: Some(".spam.near".to_string()), prefix: None, exact: None };
+
+        // nothing removed from the first window, but the sweep isn't done
+        assert_eq!(contract.remove_guests_matching(None, pattern(), 0, 2), GuestSweepJSON { removed: 0, next_index: Some(2) });
+        assert_eq!(contract.remove_guests_matching(None, pattern(), 2, 2), GuestSweepJSON { removed: 1, next_index: None });
+        assert_eq!(contract.get_event(account("alice.testnet")).guests.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_EMPTY_PATTERN")]
+    fn test_remove_guests_matching_empty_pattern() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: None }, 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_EVENT_OWNER")]
+    fn test_remove_guests_matching_not_owner() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+
+        set_predecessor(&account("bob.testnet"));
+        let pattern = GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(account("a.testnet"))) };
+        contract.remove_guests_matching(Some(account("alice.testnet")), pattern, 0, 10);
+    }
+
+    #[test]
+    fn test_upcoming_milestones() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let second = 1_000_000_000;
+        set_context(&alice, 1000 * second);
+        contract.insert_event(event_json(10, vec!()));
+
+        contract.add_event_milestone("book venue".to_string(), U64::from(1100 * second));
+        contract.add_event_milestone("print badges".to_string(), U64::from(1500 * second));
+        contract.add_event_milestone("order food".to_string(), U64::from(1050 * second));
+        contract.complete_milestone(2);
+
+        let upcoming = contract.get_events_with_upcoming_milestones(alice.clone(), 200);
+        assert_eq!(upcoming.len(), 1);
+        assert_eq!(upcoming[0].0, "alice.testnet");
+        assert_eq!(upcoming[0].1.label, "book venue");
+
+        let upcoming = contract.get_events_with_upcoming_milestones(alice.clone(), 600);
+        assert_eq!(upcoming.len(), 2);
+
+        // overdue milestones are still reported until completed
+        set_context(&alice, 2000 * second);
+        assert_eq!(contract.get_events_with_upcoming_milestones(alice.clone(), 0).len(), 2);
+        assert!(contract.get_events_with_upcoming_milestones(account("bob.testnet"), 600).is_empty());
+    }
+
+    #[test]
+    fn test_total_unique_guests() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!(account("carol.testnet"), account("dave.testnet"))));
+        set_predecessor(&account("bob.testnet"));
+        contract.insert_event(event_json(10, vec!(account("dave.testnet"), account("erin.testnet"))));
+        assert_eq!(contract.total_unique_guests(), 3);
+
+        // removed guests are still counted
+        let pattern = GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(account("erin.testnet"))) };
+        contract.remove_guests_matching(None, pattern, 0, 10);
+        assert_eq!(contract.total_unique_guests(), 3);
+    }
+
+    #[test]
+    fn test_relayed_join() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let carol = account("carol.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.preauthorize(alice.clone(), carol.clone());
+
+        set_predecessor(&account("relayer.testnet"));
+        contract.relayed_join(alice.clone(), carol.clone());
+
+        let event = contract.get_event(alice);
+        assert_eq!(event.guests, vec!(carol));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_PREAUTHORIZED")]
+    fn test_relayed_join_consumes_approval() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let carol = account("carol.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.preauthorize(alice.clone(), carol.clone());
+
+        set_predecessor(&account("relayer.testnet"));
+        contract.relayed_join(alice.clone(), carol.clone());
+        set_predecessor(&alice);
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(carol.clone())) }, 0, 10);
+        set_predecessor(&account("relayer.testnet"));
+        contract.relayed_join(alice, carol);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ALREADY_A_GUEST")]
+    fn test_relayed_join_existing_guest() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let carol = account("carol.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(carol.clone())));
+        contract.preauthorize(alice.clone(), carol.clone());
+
+        // fails before the approval is used up
+        set_predecessor(&account("relayer.testnet"));
+        contract.relayed_join(alice, carol);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_EVENT_OWNER")]
+    fn test_preauthorize_not_owner() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        set_predecessor(&account("bob.testnet"));
+        contract.preauthorize(alice, account("bob.testnet"));
+    }
+
+    fn contact_event(contract: &mut Contract) -> AccountId {
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(account("carol.testnet"), account("dave.testnet"))));
+        contract.set_contact_salt(Base64VecU8::from(b"salt".to_vec()));
+        alice
+    }
+
+    #[test]
+    fn test_contact_hashes() {
+        let mut contract = Contract::default();
+        let alice = contact_event(&mut contract);
+        set_predecessor(&account("carol.testnet"));
+        contract.register_contact_hash(alice.clone(), Base64VecU8::from(vec![1; 32]));
+        set_predecessor(&account("dave.testnet"));
+        contract.register_contact_hash(alice.clone(), Base64VecU8::from(vec![2; 32]));
+
+        set_predecessor(&alice);
+        let export = contract.export_contact_hashes(alice.clone(), 0, 10);
+        assert_eq!(export.len(), 2);
+        assert_eq!(export[0].0, account("carol.testnet"));
+        assert_eq!(export[0].1.0, vec![1; 32]);
+        assert_eq!(contract.export_contact_hashes(alice.clone(), 1, 10).len(), 1);
+
+        // removing a guest deletes their contact hash
+        let pattern = GuestPatternJSON { suffix: None, prefix: Some("carol".to_string()), exact: None };
+        contract.remove_guests_matching(None, pattern, 0, 10);
+        let export = contract.export_contact_hashes(alice.clone(), 0, 10);
+        assert_eq!(export.len(), 1);
+        assert_eq!(export[0].0, account("dave.testnet"));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_CONTACT_HASH_LENGTH")]
+    fn test_contact_hash_length() {
+        let mut contract = Contract::default();
+        let alice = contact_event(&mut contract);
+        set_predecessor(&account("carol.testnet"));
+        contract.register_contact_hash(alice, Base64VecU8::from(vec![1; 31]));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_EVENT_OWNER")]
+    fn test_contact_hash_export_owner_only() {
+        let mut contract = Contract::default();
+        let alice = contact_event(&mut contract);
+        set_predecessor(&account("carol.testnet"));
+        contract.register_contact_hash(alice.clone(), Base64VecU8::from(vec![1; 32]));
+
+        // a guest can't export the organizer's list
+        contract.export_contact_hashes(alice, 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_STORAGE_DEPOSIT")]
+    fn test_insert_event_without_deposit() {
+        let mut contract = Contract::default();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(account("alice.testnet")).build());
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"))));
+    }
+
+    #[test]
+    fn test_set_guests_refunds_unused_deposit() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        let initial_storage_usage = env::storage_usage();
+        contract.set_guests(vec!(account("bob.testnet"), account("carol.testnet")));
+        let used_bytes = env::storage_usage() - initial_storage_usage;
+        assert!(used_bytes > 0);
+
+        // the unused part of the deposit is transferred back to the caller
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        let refund = match &receipts.last().unwrap().actions[0] {
+            near_sdk::mock::VmAction::Transfer { deposit } => *deposit,
+            _ => panic!("expected a refund transfer"),
+        };
+        assert_eq!(refund, STORAGE_DEPOSIT - Balance::from(used_bytes) * env::storage_byte_cost());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        let mut event = event_json(10, vec!());
+        event.extensions.insert("app.discord.channel".to_string(), "123".to_string());
+        contract.insert_event(event);
+
+        contract.set_extension("app.stream.url".to_string(), "https://example.com/live".to_string());
+        // overwriting keeps a single entry
+        contract.set_extension("app.discord.channel".to_string(), "456".to_string());
+
+        assert_eq!(contract.get_extension(alice.clone(), "app.discord.channel".to_string(), None), Some("456".to_string()));
+        let json = near_sdk::serde_json::to_value(contract.get_event(alice.clone())).unwrap();
+        assert_eq!(json["extensions"], near_sdk::serde_json::json!({
+            "app.discord.channel": "456",
+            "app.stream.url": "https://example.com/live"
+        }));
+
+        contract.remove_extension("app.stream.url".to_string());
+        assert_eq!(contract.get_extension(alice.clone(), "app.stream.url".to_string(), None), None);
+        assert_eq!(contract.get_event(alice).extensions.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_TOO_MANY_EXTENSIONS")]
+    fn test_extensions_count_budget() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        for i in 0..16 {
+            contract.set_extension(format!("key{}", i), "value".to_string());
+        }
+        // overwriting an existing key is fine at the limit
+        contract.set_extension("key0".to_string(), "other".to_string());
+        contract.set_extension("key16".to_string(), "value".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_EXTENSION_VALUE_TOO_LONG")]
+    fn test_extensions_value_size() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_extension("key".to_string(), "v".repeat(257));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_EXTENSIONS_TOO_LARGE")]
+    fn test_extensions_total_size() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        for i in 0..9 {
+            contract.set_extension(format!("key{}", i), "v".repeat(250));
+        }
+    }
+
+    #[test]
+    fn test_events_modified_after() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        set_context(&alice, 100);
+        contract.insert_event(event_json(10, vec!()));
+        set_context(&bob, 200);
+        contract.insert_event(event_json(20, vec!()));
+        set_context(&carol, 300);
+        contract.insert_event(event_json(30, vec!()));
+
+        let modified: Vec<EventOwnerId> = contract.get_events_modified_after(U64::from(100), 10)
+            .into_iter().map(|(event_owner_id, _)| event_owner_id).collect();
+        assert_eq!(modified, vec!(bob.clone(), carol.clone()));
+
+        // a later write moves the event to the new timestamp
+        set_context(&alice, 400);
+        contract.set_guests(vec!(account("dave.testnet")));
+        let modified = contract.get_events_modified_after(U64::from(300), 10);
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].0, alice);
+        assert_eq!(modified[0].1.guests, vec!(account("dave.testnet")));
+        assert_eq!(contract.get_events_modified_after(U64::from(0), 10).len(), 3);
+        assert_eq!(contract.get_events_modified_after(U64::from(0), 2).len(), 2);
+    }
+
+    #[test]
+    fn test_guest_count_buckets() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        set_predecessor(&account("bob.testnet"));
+        contract.insert_event(event_json(10, (0..11).map(|i| account(&format!("guest{}.testnet", i))).collect()));
+        set_predecessor(&account("carol.testnet"));
+        contract.insert_event(event_json(10, vec!(account("dave.testnet"))));
+
+        assert_eq!(contract.guest_count_buckets(0, 10), vec!((0, 2), (11, 1), (101, 0), (1001, 0)));
+        // pages add up to the full histogram
+        assert_eq!(contract.guest_count_buckets(0, 2), vec!((0, 1), (11, 1), (101, 0), (1001, 0)));
+        assert_eq!(contract.guest_count_buckets(2, 2), vec!((0, 1), (11, 0), (101, 0), (1001, 0)));
+    }
+
+    fn webhook(url: &str, events: Vec<WebhookEventType>) -> WebhookRegistration {
+        WebhookRegistration { url: url.to_string(), secret: "secret".to_string(), events, active: true }
+    }
+
+    #[test]
+    fn test_webhook_logs() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.register_webhook(webhook("https://example.com/created", vec!(WebhookEventType::EventCreated)));
+        contract.register_webhook(webhook("https://example.com/guests", vec!(WebhookEventType::GuestAdded)));
+
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"))));
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 2);
+
+        let payload_json = r#"{"event_owner_id":"alice.testnet"}"#;
+        let log: near_sdk::serde_json::Value = near_sdk::serde_json::from_str(logs[0].strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+        assert_eq!(log["event"], "webhook_trigger");
+        assert_eq!(log["data"][0]["url"], "https://example.com/created");
+        assert_eq!(log["data"][0]["payload_json"], payload_json);
+        assert_eq!(log["data"][0]["hmac_hint"], crate::webhooks::hmac_hint("secret", payload_json));
+        assert_eq!(log["data"][0]["hmac_hint"].as_str().unwrap().len(), 16);
+        assert!(logs[1].contains("https://example.com/guests") && logs[1].contains("bob.testnet"));
+
+        // deregistered webhooks are no longer triggered
+        set_predecessor(&alice);
+        contract.deregister_webhook("https://example.com/guests".to_string());
+        contract.set_guests(vec!(account("carol.testnet")));
+        assert!(near_sdk::test_utils::get_logs().is_empty());
+        let webhooks = near_sdk::serde_json::to_value(contract.get_webhooks(alice)).unwrap();
+        assert_eq!(webhooks, json!([{ "url": "https://example.com/created", "events": ["event_created"], "active": true }]));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_TOO_MANY_WEBHOOKS")]
+    fn test_webhook_limit() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        for i in 0..6 {
+            contract.register_webhook(webhook(&format!("https://example.com/{}", i), vec!(WebhookEventType::GuestAdded)));
+        }
+    }
+
+    fn set_random_seed(account_id: &AccountId, random_seed: [u8; 32]) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(STORAGE_DEPOSIT)
+            .random_seed(random_seed)
+            .build());
+    }
+
+    // secrets are generated off-chain, the contract only gets their hashes
+    fn invite_hashes(secrets: &[&[u8]]) -> Vec<Base64VecU8> {
+        secrets.iter().map(|secret| Base64VecU8::from(env::sha256(secret))).collect()
+    }
+
+    #[test]
+    fn test_one_time_invites() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_one_time_invites(invite_hashes(&[b"one", b"two", b"three"]));
+        // a hash that is already pending isn't counted twice
+        contract.create_one_time_invites(invite_hashes(&[b"three", b"four"]));
+        assert_eq!(contract.pending_invites_count(alice.clone()), 4);
+
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_invite(alice.clone(), Base64VecU8::from(b"one".to_vec()));
+        assert_eq!(contract.get_event(alice.clone()).guests, vec!(account("bob.testnet")));
+        assert_eq!(contract.pending_invites_count(alice.clone()), 3);
+
+        set_predecessor(&alice);
+        assert_eq!(contract.revoke_invites(), 3);
+        assert_eq!(contract.pending_invites_count(alice), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVITE_ALREADY_USED")]
+    fn test_one_time_invite_reuse() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_one_time_invites(invite_hashes(&[b"secret"]));
+
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_invite(alice.clone(), Base64VecU8::from(b"secret".to_vec()));
+        set_predecessor(&account("carol.testnet"));
+        contract.redeem_invite(alice, Base64VecU8::from(b"secret".to_vec()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ALREADY_A_GUEST")]
+    fn test_one_time_invite_existing_guest() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone())));
+        contract.create_one_time_invites(invite_hashes(&[b"secret"]));
+
+        // fails before the invite is burned
+        set_predecessor(&bob);
+        contract.redeem_invite(alice, Base64VecU8::from(b"secret".to_vec()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVITE_ALREADY_USED")]
+    fn test_one_time_invite_resubmitted() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_one_time_invites(invite_hashes(&[b"secret"]));
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_invite(alice.clone(), Base64VecU8::from(b"secret".to_vec()));
+
+        set_predecessor(&alice);
+        contract.create_one_time_invites(invite_hashes(&[b"secret"]));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_INVITE_HASH")]
+    fn test_one_time_invite_hash_length() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_one_time_invites(vec!(Base64VecU8::from(b"not a hash".to_vec())));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_INVITE")]
+    fn test_one_time_invite_revoked() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_one_time_invites(invite_hashes(&[b"one", b"two"]));
+        contract.revoke_invites();
+
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_invite(alice, Base64VecU8::from(b"two".to_vec()));
+    }
+
+    #[test]
+    fn test_event_localization() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_event_localization("en".to_string(), EventLocalization {
+            title: "Party".to_string(),
+            description: "Welcome".to_string(),
+        });
+        contract.set_event_localization("fr".to_string(), EventLocalization {
+            title: "Fête".to_string(),
+            description: "Bienvenue".to_string(),
+        });
+
+        assert_eq!(contract.get_event_localized(alice.clone(), "en".to_string(), None).unwrap().title, "Party");
+        let french = contract.get_event_localized(alice.clone(), "fr".to_string(), None).unwrap();
+        assert_eq!(french.title, "Fête");
+        assert_eq!(french.description, "Bienvenue");
+        assert!(contract.get_event_localized(alice.clone(), "de".to_string(), None).is_none());
+        assert_eq!(contract.get_available_languages(alice), vec!("en".to_string(), "fr".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_LANGUAGE_CODE")]
+    fn test_event_localization_invalid_code() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_event_localization("english".to_string(), EventLocalization {
+            title: "Party".to_string(),
+            description: "Welcome".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_event_localization_after_reinsert() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        let localization = |title: &str| EventLocalization { title: title.to_string(), description: String::new() };
+        contract.set_event_localization("en".to_string(), localization("Party"));
+
+        contract.insert_event(event_json(20, vec!()));
+        assert_eq!(contract.get_available_languages(alice.clone()), vec!("en".to_string()));
+        // the same language code is overwritten rather than indexed twice
+        contract.set_event_localization("en".to_string(), localization("Big party"));
+        assert_eq!(contract.get_available_languages(alice.clone()), vec!("en".to_string()));
+        assert_eq!(contract.get_event_localized(alice, "en".to_string(), None).unwrap().title, "Big party");
+    }
+
+    #[test]
+    fn test_verified_organizer() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        let json = near_sdk::serde_json::to_value(contract.get_event(alice.clone())).unwrap();
+        assert_eq!(json["organizerVerified"], false);
+
+        // the contract account is the admin
+        set_predecessor(&env::current_account_id());
+        contract.verify_organizer(alice.clone());
+        assert!(contract.is_organizer_verified(alice.clone()));
+        let json = near_sdk::serde_json::to_value(contract.get_event(alice.clone())).unwrap();
+        assert_eq!(json["organizerVerified"], true);
+
+        contract.unverify_organizer(alice.clone());
+        assert!(!contract.get_event(alice).organizer_verified);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ADMIN")]
+    fn test_verify_organizer_not_admin() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.verify_organizer(alice);
+    }
+
+    #[test]
+    fn test_format_near() {
+        let near = 1_000_000_000_000_000_000_000_000;
+        assert_eq!(format_near(0, 2), "0");
+        assert_eq!(format_near(near, 2), "1");
+        assert_eq!(format_near(near * 3 / 2, 2), "1.5");
+        assert_eq!(format_near(near * 3 / 2, 0), "1");
+        // truncated, never rounded up
+        assert_eq!(format_near(999_999_999_999_999_999_999_999, 2), "0.99");
+        assert_eq!(format_near(999_999_999_999_999_999_999_999, 0), "0");
+        assert_eq!(format_near(999_999_999_999_999_999_999_999, 24), "0.999999999999999999999999");
+        assert_eq!(format_near(1_009_000_000_000_000_000_000_000, 2), "1");
+        assert_eq!(format_near(1, 24), "0.000000000000000000000001");
+        assert_eq!(format_near(1, 23), "0");
+        assert_eq!(format_near(1_250_000_000_000_000_000_000_000, 24), "1.25");
+        // more than 24 decimals behaves like 24
+        assert_eq!(format_near(1, 30), "0.000000000000000000000001");
+        assert_eq!(format_near(u128::MAX, 24), "340282366920938.463463374607431768211455");
+    }
+
+    #[test]
+    fn test_parse_near() {
+        let near = 1_000_000_000_000_000_000_000_000;
+        assert_eq!(parse_near("0"), Ok(0));
+        assert_eq!(parse_near("1"), Ok(near));
+        assert_eq!(parse_near("1.5"), Ok(near * 3 / 2));
+        assert_eq!(parse_near("0.999999999999999999999999"), Ok(999_999_999_999_999_999_999_999));
+        assert_eq!(parse_near("0.000000000000000000000001"), Ok(1));
+        assert_eq!(parse_near("340282366920938.463463374607431768211455"), Ok(u128::MAX));
+
+        assert_eq!(parse_near(""), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("."), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("1."), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near(".5"), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("-1"), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("1.2.3"), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("1e24"), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("0.0000000000000000000000001"), Err(ContractError::InvalidAmount));
+        assert_eq!(parse_near("340282366920938.463463374607431768211456"), Err(ContractError::AmountOverflow));
+        assert_eq!(parse_near("340282366920939"), Err(ContractError::AmountOverflow));
+
+        // round trip at full precision
+        for yocto in [0, 1, near, 999_999_999_999_999_999_999_999, u128::MAX] {
+            assert_eq!(parse_near(&format_near(yocto, 24)), Ok(yocto));
+        }
+    }
+
+    #[test]
+    fn test_price_display() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(1_234_567_000_000_000_000_000_000, vec!()));
+        assert_eq!(contract.get_event(alice.clone()).price_display, "1.23");
+
+        contract.set_display_decimals(4);
+        assert_eq!(contract.get_event(alice).price_display, "1.2345");
+    }
+
+    #[test]
+    fn test_promo_codes() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_promo_code("OPEN".to_string(), None, 2);
+        contract.create_promo_code("SPONSOR".to_string(), Some(account("carol.testnet")), 1);
+
+        // open codes work for anyone
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_promo_code(alice.clone(), "OPEN".to_string());
+        set_predecessor(&account("dave.testnet"));
+        contract.redeem_promo_code(alice.clone(), "OPEN".to_string());
+        set_predecessor(&account("carol.testnet"));
+        contract.redeem_promo_code(alice.clone(), "SPONSOR".to_string());
+
+        let mut guests: Vec<String> = contract.get_event(alice).guests.iter().map(|guest| guest.to_string()).collect();
+        guests.sort();
+        assert_eq!(guests, vec!["bob.testnet", "carol.testnet", "dave.testnet"]);
+    }
+
+    #[test]
+    fn test_promo_codes_after_reinsert() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_promo_code("OPEN".to_string(), None, 2);
+
+        contract.insert_event(event_json(20, vec!()));
+        assert_eq!(contract.get_events_with_active_promotions(0, 10)[0].2, 1);
+        // recreating the code replaces it
+        contract.create_promo_code("OPEN".to_string(), None, 1);
+        assert_eq!(contract.get_events_with_active_promotions(0, 10)[0].2, 1);
+
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_promo_code(alice.clone(), "OPEN".to_string());
+        assert_eq!(contract.get_event(alice).guests, vec!(account("bob.testnet")));
+        assert!(contract.get_events_with_active_promotions(0, 10).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_PROMO_CODE_WRONG_RECIPIENT")]
+    fn test_promo_code_wrong_recipient() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_promo_code("SPONSOR".to_string(), Some(account("carol.testnet")), 1);
+
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_promo_code(alice, "SPONSOR".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_PROMO_CODE_EXHAUSTED")]
+    fn test_promo_code_exhausted() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_promo_code("OPEN".to_string(), None, 1);
+
+        set_predecessor(&account("bob.testnet"));
+        contract.redeem_promo_code(alice.clone(), "OPEN".to_string());
+        set_predecessor(&account("dave.testnet"));
+        contract.redeem_promo_code(alice, "OPEN".to_string());
+    }
+
+    #[test]
+    fn test_code_info() {
+        let contract = Contract::default();
+        let code_info = contract.get_code_info();
+        assert!(!code_info.commit.is_empty());
+        assert!(code_info.rustc.starts_with("rustc "));
+        assert_eq!(code_info.dirty, crate::code_info::BUILD_GIT_DIRTY == "true");
+        assert!(["true", "false"].contains(&crate::code_info::BUILD_GIT_DIRTY));
+        assert!(code_info.code_hash.is_none());
+        assert_eq!(contract.state_version(), STATE_VERSION);
+    }
+
+    #[test]
+    fn test_upgrade_records_code_hash() {
+        let code = b"new contract code".to_vec();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(env::current_account_id())
+            .build());
+        env::state_write(&Contract::default());
+        let mut context = VMContextBuilder::new()
+            .predecessor_account_id(env::current_account_id())
+            .build();
+        context.input = code.clone();
+        testing_env!(context);
+
+        crate::code_info::upgrade();
+
+        let contract: Contract = env::state_read().unwrap();
+        assert_eq!(contract.get_code_info().code_hash.unwrap().0, env::sha256(&code));
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert!(matches!(&receipts[0].actions[0], near_sdk::mock::VmAction::DeployContract { code: deployed } if deployed == &code));
+    }
+
+    #[test]
+    fn test_migrate_unversioned_state() {
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        testing_env!(VMContextBuilder::new().predecessor_account_id(env::current_account_id()).build());
+        let mut events = LookupMap::new(StorageKey::Events);
+        for (event_owner_id, price) in [(&alice, 10), (&bob, 20)] {
+            let mut guests = UnorderedSet::new(StorageKey::Guests { event_owner_id: event_owner_id.clone() });
+            guests.insert(&account("carol.testnet"));
+            events.insert(event_owner_id, &crate::migration::EventV0 { price, guests });
+        }
+        env::state_write(&crate::migration::ContractV0 { events });
+
+        let contract = Contract::migrate(vec!(alice.clone(), bob.clone()));
+        assert_eq!(contract.state_version(), STATE_VERSION);
+        let event = contract.get_event(alice);
+        assert_eq!((event.price.0, event.guests), (10, vec!(account("carol.testnet"))));
+        assert_eq!(contract.get_event(bob).price.0, 20);
+        assert_eq!(contract.total_unique_guests(), 1);
+        assert_eq!(contract.get_network_contribution().total_events, 2);
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        use crate::migration::{ContractV1, EventV1, PromoCodeV1};
+        let alice = account("alice.testnet");
+        testing_env!(VMContextBuilder::new().predecessor_account_id(env::current_account_id()).build());
+        let mut old_event = EventV1 {
+            owner_id: alice.clone(),
+            price: 10,
+            guests: UnorderedSet::new(StorageKey::Guests { event_owner_id: alice.clone() }),
+            slug: Some("party".to_string()),
+            guest_sweep_cursor: 0,
+            milestones: vec!(),
+            preauthorized: LookupSet::new(StorageKey::Preauthorized { event_owner_id: alice.clone() }),
+            contact_salt: None,
+            contact_hashes: UnorderedMap::new(StorageKey::ContactHashes { event_owner_id: alice.clone() }),
+            extensions: UnorderedMap::new(StorageKey::Extensions { event_owner_id: alice.clone() }),
+            last_modified_at: 100,
+            invites: UnorderedSet::new(StorageKey::Invites { event_owner_id: alice.clone() }),
+            redeemed_invites: LookupSet::new(StorageKey::RedeemedInvites { event_owner_id: alice.clone() }),
+            invite_nonce: 0,
+            localizations: UnorderedMap::new(StorageKey::Localizations { event_owner_id: alice.clone() }),
+            display_decimals: 2,
+            promo_codes: UnorderedMap::new(StorageKey::PromoCodes { event_owner_id: alice.clone() }),
+        };
+        old_event.guests.insert(&account("bob.testnet"));
+        old_event.extensions.insert(&"app.discord".to_string(), &"general".to_string());
+        old_event.promo_codes.insert(&"VIP".to_string(), &PromoCodeV1 { recipient: None, uses_remaining: 1, is_complimentary: true });
+        old_event.promo_codes.insert(&"USED".to_string(), &PromoCodeV1 { recipient: None, uses_remaining: 0, is_complimentary: true });
+        let mut old_state = ContractV1 {
+            events: UnorderedMap::new(StorageKey::Events),
+            slugs: UnorderedMap::new(StorageKey::Slugs),
+            unique_guests: UnorderedSet::new(StorageKey::UniqueGuests),
+            verified_organizers: UnorderedSet::new(StorageKey::VerifiedOrganizers),
+            webhooks: UnorderedMap::new(StorageKey::Webhooks),
+            code_hash: None,
+            version: 1,
+            events_by_modification_time: TreeMap::new(StorageKey::EventsByModificationTime),
+        };
+        old_state.events.insert(&alice, &old_event);
+        old_state.slugs.insert(&"party".to_string(), &alice);
+        old_state.unique_guests.insert(&account("bob.testnet"));
+        env::state_write(&old_state);
+
+        let mut contract = Contract::migrate(vec!());
+        assert_eq!(contract.state_version(), STATE_VERSION);
+        let event = contract.get_event_by_slug("party".to_string()).unwrap();
+        assert_eq!((event.price.0, event.guests), (10, vec!(account("bob.testnet"))));
+        assert_eq!(contract.get_extension(alice.clone(), "app.discord".to_string(), None), Some("general".to_string()));
+        assert_eq!(contract.total_unique_guests(), 1);
+
+        // promo codes are rewritten in the new layout and indexed
+        assert_eq!(contract.get_events_with_active_promotions(0, 10)[0].2, 1);
+        set_predecessor(&account("carol.testnet"));
+        contract.redeem_promo_code(alice.clone(), "VIP".to_string());
+        assert_eq!(contract.get_event(alice).guests.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ALREADY_MIGRATED")]
+    fn test_migrate_current_state() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(env::current_account_id()).build());
+        env::state_write(&Contract::default());
+        Contract::migrate(vec!());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_UNKNOWN_STATE_VERSION: 7")]
+    fn test_migrate_unknown_version() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(env::current_account_id()).build());
+        env::state_write(&crate::migration::ContractV1 {
+            events: UnorderedMap::new(StorageKey::Events),
+            slugs: UnorderedMap::new(StorageKey::Slugs),
+            unique_guests: UnorderedSet::new(StorageKey::UniqueGuests),
+            verified_organizers: UnorderedSet::new(StorageKey::VerifiedOrganizers),
+            webhooks: UnorderedMap::new(StorageKey::Webhooks),
+            code_hash: None,
+            version: 7,
+            events_by_modification_time: TreeMap::new(StorageKey::EventsByModificationTime),
+        });
+        Contract::migrate(vec!());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ADMIN")]
+    fn test_migrate_not_admin() {
+        set_predecessor(&account("alice.testnet"));
+        env::state_write(&Contract::default());
+        Contract::migrate(vec!());
+    }
+
+    #[test]
+    fn test_top_events_by_attendance() {
+        let mut contract = Contract::default();
+        for (owner, guests_count) in [("a", 3), ("b", 7), ("c", 0), ("d", 5), ("e", 1)] {
+            set_predecessor(&account(&format!("{}.testnet", owner)));
+            let guests = (0..guests_count).map(|i| account(&format!("guest{}.testnet", i))).collect();
+            contract.insert_event(event_json(10, guests));
+        }
+
+        let top: Vec<(String, u64)> = contract.get_top_events_by_attendance(3)
+            .into_iter()
+            .map(|(event_owner_id, event, guests_count)| {
+                assert_eq!(event.guests.len() as u64, guests_count);
+                (event_owner_id.to_string(), guests_count)
+            })
+            .collect();
+        assert_eq!(top, vec!(("b.testnet".to_string(), 7), ("d.testnet".to_string(), 5), ("a.testnet".to_string(), 3)));
+        assert_eq!(contract.get_top_events_by_attendance(100).len(), 5);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        for owner in [&alice, &bob, &carol] {
+            set_predecessor(owner);
+            contract.insert_event(event_json(10, vec!()));
+        }
+
+        let dave = account("dave.testnet");
+        set_predecessor(&dave);
+        contract.bookmark_event(alice.clone());
+        contract.bookmark_event(bob.clone());
+        contract.bookmark_event(carol.clone());
+        set_predecessor(&account("erin.testnet"));
+        contract.bookmark_event(alice.clone());
+
+        assert_eq!(contract.get_event(alice.clone()).bookmarks_count, 2);
+        assert_eq!(contract.get_bookmarks(dave.clone(), 0, 2), vec!(alice.clone(), bob.clone()));
+        assert_eq!(contract.get_bookmarks(dave.clone(), 2, 2), vec!(carol.clone()));
+        assert!(contract.get_bookmarks(account("frank.testnet"), 0, 10).is_empty());
+
+        // not joined by bookmarking
+        assert!(contract.get_event(alice.clone()).guests.is_empty());
+
+        set_predecessor(&dave);
+        contract.remove_bookmark(alice.clone());
+        assert_eq!(contract.get_event(alice.clone()).bookmarks_count, 1);
+        assert_eq!(contract.get_bookmarks(dave, 0, 10).len(), 2);
+
+        // re-inserting the event keeps its bookmarks
+        set_predecessor(&alice);
+        contract.insert_event(event_json(20, vec!()));
+        assert_eq!(contract.get_event(alice).bookmarks_count, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ALREADY_BOOKMARKED")]
+    fn test_double_bookmark() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        set_predecessor(&account("dave.testnet"));
+        contract.bookmark_event(alice.clone());
+        contract.bookmark_event(alice);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_BOOKMARKED")]
+    fn test_remove_missing_bookmark() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.bookmark_event(alice.clone());
+        contract.remove_bookmark(alice.clone());
+        contract.remove_bookmark(alice);
+    }
+
+    #[test]
+    fn test_network_contribution() {
+        testing_env!(VMContextBuilder::new().block_index(100).build());
+        let mut contract = Contract::default();
+
+        let contribution = contract.get_network_contribution();
+        assert_eq!(contribution.contract_id, env::current_account_id());
+        assert_eq!(contribution.total_events, 0);
+        assert_eq!(contribution.active_since_block, 100);
+
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        set_predecessor(&account("bob.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        set_predecessor(&account("bob.testnet"));
+        contract.insert_event(event_json(20, vec!()));
+
+        let contribution = contract.get_network_contribution();
+        assert_eq!(contribution.total_events, 2);
+        assert_eq!(contribution.active_since_block, 100);
+    }
+
+    #[test]
+    fn test_validate_event_json_price() {
+        let errors = validate_and_sanitize_event_json(event_json(0, vec!())).unwrap_err();
+        assert_eq!(errors, vec!(ValidationError { field: "price".to_string(), message: "ERR_ZERO_PRICE".to_string() }));
+    }
+
+    #[test]
+    fn test_validate_event_json_dedups_guests() {
+        let (bob, carol) = (account("bob.testnet"), account("carol.testnet"));
+        let event = validate_and_sanitize_event_json(
+            event_json(10, vec!(bob.clone(), carol.clone(), bob.clone()))).unwrap();
+        assert_eq!(event.guests, vec!(bob, carol));
+    }
+
+    #[test]
+    fn test_event_json_rejects_unnormalized_guests() {
+        // so validate_and_sanitize_event_json never sees them
+        for guest in ["Bob.testnet", " bob.testnet"] {
+            let raw = format!(r#"{{"price":"10","guests":["{}"]}}"#, guest);
+            assert!(near_sdk::serde_json::from_str::<EventJSON>(&raw).is_err(), "{} accepted", guest);
+        }
+    }
+
+    #[test]
+    fn test_validate_event_json_trims_extensions() {
+        let mut raw = event_json(10, vec!());
+        raw.extensions.insert(" app.discord ".to_string(), "  general ".to_string());
+        let event = validate_and_sanitize_event_json(raw).unwrap();
+        assert_eq!(event.extensions.get("app.discord").unwrap(), "general");
+
+        let mut raw = event_json(10, vec!());
+        raw.extensions.insert("app.discord".to_string(), "a".to_string());
+        raw.extensions.insert("app.discord ".to_string(), "b".to_string());
+        let errors = validate_and_sanitize_event_json(raw).unwrap_err();
+        assert_eq!(errors[0].message, "ERR_DUPLICATE_EXTENSION_KEY");
+    }
+
+    #[test]
+    fn test_validate_event_json_extension_limits() {
+        let mut raw = event_json(10, vec!());
+        raw.extensions.insert("   ".to_string(), "a".to_string());
+        assert_eq!(validate_and_sanitize_event_json(raw).unwrap_err()[0].message, "ERR_EXTENSION_KEY_TOO_LONG");
+
+        let mut raw = event_json(10, vec!());
+        raw.extensions.insert("key".to_string(), "v".repeat(257));
+        assert_eq!(validate_and_sanitize_event_json(raw).unwrap_err()[0].message, "ERR_EXTENSION_VALUE_TOO_LONG");
+
+        let mut raw = event_json(10, vec!());
+        for i in 0..17 {
+            raw.extensions.insert(format!("key{}", i), "v".to_string());
+        }
+        assert_eq!(validate_and_sanitize_event_json(raw).unwrap_err()[0].message, "ERR_TOO_MANY_EXTENSIONS");
+
+        // same total size budget as `insert_event`
+        let mut raw = event_json(10, vec!());
+        for i in 0..9 {
+            raw.extensions.insert(format!("key{}", i), "v".repeat(250));
+        }
+        assert_eq!(validate_and_sanitize_event_json(raw).unwrap_err()[0].message, "ERR_EXTENSIONS_TOO_LARGE");
+    }
+
+    #[test]
+    fn test_validate_event_json_collects_all_errors() {
+        let mut raw = event_json(0, vec!());
+        raw.extensions.insert("k".repeat(33), "v".repeat(257));
+        let fields: Vec<String> = validate_and_sanitize_event_json(raw).unwrap_err()
+            .into_iter().map(|error| error.message).collect();
+        assert_eq!(fields, vec!("ERR_ZERO_PRICE", "ERR_EXTENSION_KEY_TOO_LONG", "ERR_EXTENSION_VALUE_TOO_LONG"));
+    }
+
+    #[test]
+    fn test_insert_event_legacy() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+
+        set_predecessor(&alice);
+        contract.insert_event_legacy(near_sdk::serde_json::from_str(
+            r#"{"price": 10, "guest_list": ["carol.testnet", "dave.testnet"]}"#).unwrap());
+        assert!(near_sdk::test_utils::get_logs().iter().any(|log| log.contains("\"event\":\"deprecated_payload\"")));
+
+        set_predecessor(&bob);
+        contract.insert_event(near_sdk::serde_json::from_str(
+            r#"{"price": "10", "guests": ["carol.testnet", "dave.testnet"]}"#).unwrap());
+        assert!(!near_sdk::test_utils::get_logs().iter().any(|log| log.contains("deprecated_payload")));
+
+        assert_eq!(
+            near_sdk::serde_json::to_string(&contract.get_event(alice)).unwrap(),
+            near_sdk::serde_json::to_string(&contract.get_event(bob)).unwrap());
+    }
+
+    #[test]
+    fn test_get_events_without_guests() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        set_predecessor(&bob);
+        contract.insert_event(event_json(10, vec!(account("dave.testnet"))));
+        set_predecessor(&carol);
+        contract.insert_event(event_json(10, vec!()));
+
+        let owners: Vec<AccountId> = contract.get_events_without_guests(None, 0, 10)
+            .into_iter().map(|(event_owner_id, _)| event_owner_id).collect();
+        assert_eq!(owners, vec!(alice.clone(), carol.clone()));
+        assert_eq!(contract.get_events_without_guests(None, 1, 10)[0].0, carol);
+
+        assert_eq!(contract.get_events_without_guests(Some(alice.clone()), 0, 10)[0].0, alice);
+        assert!(contract.get_events_without_guests(Some(bob), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_events_by_price_sorted() {
+        let mut contract = Contract::default();
+        for (owner, price) in [("alice.testnet", 20), ("bob.testnet", 5), ("carol.testnet", 10)] {
+            set_predecessor(&account(owner));
+            contract.insert_event(event_json(price, vec!()));
+        }
+
+        let prices = |events: Vec<(EventOwnerId, EventJSON)>| events.iter().map(|(_, event)| event.price.0).collect::<Vec<Balance>>();
+        assert_eq!(prices(contract.events_by_price_sorted(true, 0, 10)), vec!(5, 10, 20));
+        let owners: Vec<EventOwnerId> = contract.events_by_price_sorted(true, 0, 10).into_iter().map(|(event_owner_id, _)| event_owner_id).collect();
+        assert_eq!(owners, vec!(account("bob.testnet"), account("carol.testnet"), account("alice.testnet")));
+        assert_eq!(prices(contract.events_by_price_sorted(false, 0, 10)), vec!(20, 10, 5));
+        assert_eq!(prices(contract.events_by_price_sorted(true, 1, 1)), vec!(10));
+    }
+
+    #[test]
+    fn test_method_keys() {
+        let mut contract = Contract::default();
+        let public_key: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+        set_predecessor(&env::current_account_id());
+        contract.grant_method_key(public_key.clone(), vec!("set_guests".to_string()), U128::from(STORAGE_DEPOSIT));
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert!(matches!(&receipts[0].actions[0], near_sdk::mock::VmAction::AddKeyWithFunctionCall {
+            public_key: key, allowance: Some(allowance), receiver_id, function_names, ..
+        } if key == &public_key && *allowance == STORAGE_DEPOSIT && receiver_id == &env::current_account_id()
+            && function_names == &vec!("set_guests".to_string())));
+        let grants = contract.get_key_grants(0, 10);
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].1.methods, vec!("set_guests".to_string()));
+
+        set_predecessor(&env::current_account_id());
+        contract.revoke_method_key(public_key.clone());
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert!(matches!(&receipts[0].actions[0], near_sdk::mock::VmAction::DeleteKey { public_key: key } if key == &public_key));
+        assert!(contract.get_key_grants(0, 10).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ADMIN_METHOD")]
+    fn test_method_key_for_admin_method() {
+        let mut contract = Contract::default();
+        set_predecessor(&env::current_account_id());
+        contract.grant_method_key("ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap(),
+            vec!("set_guests".to_string(), "upgrade".to_string()), U128::from(STORAGE_DEPOSIT));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_UNKNOWN_KEY_GRANT")]
+    fn test_revoke_unknown_method_key() {
+        let mut contract = Contract::default();
+        set_predecessor(&env::current_account_id());
+        contract.revoke_method_key("ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ADMIN")]
+    fn test_method_key_not_admin() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.grant_method_key("ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap(),
+            vec!("set_guests".to_string()), U128::from(STORAGE_DEPOSIT));
+    }
+
+    #[test]
+    fn test_emergency_withdraw() {
+        let mut contract = Contract::default();
+        let bob = account("bob.testnet");
+        set_predecessor(&env::current_account_id());
+        contract.emergency_withdraw(U128::from(STORAGE_DEPOSIT), bob.clone());
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert!(matches!(receipts[0].actions[0], near_sdk::mock::VmAction::Transfer { deposit } if deposit == STORAGE_DEPOSIT));
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with("EVENT_JSON:") && logs[0].contains("\"event\":\"emergency_withdraw\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ADMIN")]
+    fn test_emergency_withdraw_not_admin() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.emergency_withdraw(U128::from(STORAGE_DEPOSIT), alice);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_BALANCE")]
+    fn test_emergency_withdraw_too_much() {
+        let mut contract = Contract::default();
+        set_predecessor(&env::current_account_id());
+        contract.emergency_withdraw(U128::from(env::account_balance() + 1), account("bob.testnet"));
+    }
+
+    #[test]
+    fn test_set_guests_lenient() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"))));
+
+        let results = contract.set_guests_lenient(vec!(
+            "carol.testnet".to_string(),
+            "bob.testnet".to_string(),
+            "Not An Account".to_string(),
+            "carol.testnet".to_string(),
+            "dave.testnet".to_string(),
+        ));
+        let statuses: Vec<GuestStatus> = results.into_iter().map(|result| result.status).collect();
+        assert_eq!(statuses, vec!(GuestStatus::Added, GuestStatus::Duplicate, GuestStatus::Invalid,
+            GuestStatus::Duplicate, GuestStatus::Added));
+        assert_eq!(contract.get_event(alice).guests,
+            vec!(account("bob.testnet"), account("carol.testnet"), account("dave.testnet")));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_BATCH_TOO_LARGE")]
+    fn test_set_guests_lenient_batch_too_large() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_guests_lenient((0..501).map(|i| format!("guest{}.testnet", i)).collect());
+    }
+
+    #[test]
+    fn test_ticket_numbers() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let (bob, carol, dave) = (account("bob.testnet"), account("carol.testnet"), account("dave.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+        assert_eq!(contract.ticket_number(alice.clone(), bob.clone()), Some(1));
+        assert_eq!(contract.ticket_number(alice.clone(), carol.clone()), Some(2));
+
+        // numbers of removed guests are not reused
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(carol.clone())) }, 0, 10);
+        assert_eq!(contract.ticket_number(alice.clone(), carol.clone()), None);
+        contract.set_guests(vec!(dave.clone(), carol.clone()));
+        assert_eq!(contract.ticket_number(alice.clone(), dave), Some(3));
+        assert_eq!(contract.ticket_number(alice.clone(), carol), Some(4));
+        // existing guests keep their number
+        assert_eq!(contract.ticket_number(alice, bob), Some(1));
+    }
+
+    #[test]
+    fn test_ticket_numbers_after_reinsert() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let (bob, carol) = (account("bob.testnet"), account("carol.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+
+        // a guest dropped by the re-insert loses their number, kept guests keep theirs
+        contract.insert_event(event_json(10, vec!(carol.clone())));
+        assert_eq!(contract.ticket_number(alice.clone(), bob.clone()), None);
+        assert_eq!(contract.ticket_number(alice.clone(), carol), Some(2));
+        contract.insert_event(event_json(10, vec!(bob.clone())));
+        assert_eq!(contract.ticket_number(alice, bob), Some(3));
+    }
+
+    #[test]
+    fn test_domain_restrictions() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_domain_restrictions(vec!("Testnet".to_string()));
+        contract.set_guests(vec!(account("bob.testnet")));
+
+        let results = contract.set_guests_lenient(vec!("carol.testnet".to_string(), "carol.mainnet".to_string()));
+        assert_eq!(results[0].status, GuestStatus::Added);
+        assert_eq!(results[1].status, GuestStatus::DomainNotAllowed);
+
+        // the restriction survives re-inserting the event
+        contract.insert_event(event_json(10, vec!(account("dave.testnet"))));
+        contract.set_domain_restrictions(vec!());
+        contract.set_guests(vec!(account("dave.mainnet")));
+        assert_eq!(contract.get_event(alice).guests.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DOMAIN_NOT_ALLOWED")]
+    fn test_domain_not_allowed() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_domain_restrictions(vec!("testnet".to_string()));
+        contract.set_guests(vec!(account("alice.mainnet")));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_TOO_MANY_DOMAINS")]
+    fn test_too_many_domains() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_domain_restrictions((0..6).map(|i| format!("domain{}.near", i)).collect());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_DOMAIN")]
+    fn test_domain_too_long() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_domain_restrictions(vec!(format!("{}.near", "a".repeat(60))));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_STORAGE_DEPOSIT")]
+    fn test_domain_restrictions_without_deposit() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        testing_env!(VMContextBuilder::new().predecessor_account_id(alice).build());
+        contract.set_domain_restrictions(vec!("university.edu".to_string()));
+    }
+
+    #[test]
+    fn test_draw_winners() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let guests: Vec<AccountId> = (0..10).map(|i| account(&format!("guest{}.testnet", i))).collect();
+        set_random_seed(&alice, [7; 32]);
+        contract.insert_event(event_json(10, guests.clone()));
+
+        let winners = contract.draw_winners(alice.clone(), 3);
+        assert_eq!(winners.len(), 3);
+        assert!(winners.iter().all(|winner| guests.contains(winner)));
+        assert!(winners[0] != winners[1] && winners[1] != winners[2] && winners[0] != winners[2]);
+        assert_eq!(contract.get_raffle_winners(alice), Some(winners));
+        assert!(near_sdk::test_utils::get_logs().iter().any(|log| log.contains("\"event\":\"raffle_winners\"")));
+    }
+
+    #[test]
+    fn test_draw_all_guests() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let guests: Vec<AccountId> = (0..10).map(|i| account(&format!("guest{}.testnet", i))).collect();
+        set_random_seed(&alice, [3; 32]);
+        contract.insert_event(event_json(10, guests.clone()));
+
+        // every guest is drawn exactly once
+        let mut winners = contract.draw_winners(alice, 10);
+        winners.sort();
+        assert_eq!(winners, guests);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_WINNERS_ALREADY_DRAWN")]
+    fn test_draw_winners_twice() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"), account("carol.testnet"))));
+        contract.draw_winners(alice.clone(), 1);
+        // re-inserting the event doesn't allow a re-roll
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"), account("carol.testnet"))));
+        contract.draw_winners(alice, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ENOUGH_GUESTS")]
+    fn test_draw_too_many_winners() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"))));
+        contract.draw_winners(alice, 2);
+    }
+
+    #[test]
+    fn test_guest_provenance() {
+        let mut contract = Contract::default();
+        let (alice, bob, relayer) = (account("alice.testnet"), account("bob.testnet"), account("relayer.testnet"));
+        set_context(&alice, 100);
+        contract.insert_event(event_json(10, vec!(bob.clone())));
+        let provenance = contract.get_guest_provenance(alice.clone(), bob.clone()).unwrap();
+        assert_eq!((provenance.added_by, provenance.added_at.0, provenance.via), (alice.clone(), 100, JoinMethod::OwnerSet));
+        assert_eq!(near_sdk::serde_json::to_value(JoinMethod::OwnerSet).unwrap(), "owner_set");
+
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(bob.clone())) }, 0, 10);
+        assert!(contract.get_guest_provenance(alice.clone(), bob.clone()).is_none());
+
+        // re-added by a relayer, only the latest addition is kept
+        contract.preauthorize(alice.clone(), bob.clone());
+        set_context(&relayer, 200);
+        contract.relayed_join(alice.clone(), bob.clone());
+        let provenance = contract.get_guest_provenance(alice, bob).unwrap();
+        assert_eq!((provenance.added_by, provenance.added_at.0, provenance.via), (relayer, 200, JoinMethod::Relayed));
+    }
+
+    #[test]
+    fn test_guest_provenance_after_reinsert() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+
+        contract.insert_event(event_json(10, vec!(carol.clone())));
+        assert!(contract.get_guest_provenance(alice.clone(), bob).is_none());
+        assert_eq!(contract.get_guest_provenance(alice.clone(), carol).unwrap().added_by, alice);
+    }
+
+    #[test]
+    fn test_diff_guests() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        let (bob, carol, dave, erin) = (account("bob.testnet"), account("carol.testnet"), account("dave.testnet"), account("erin.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+
+        let diff = contract.diff_guests(alice, vec!(erin.clone(), carol, dave.clone(), erin.clone()));
+        assert_eq!(diff.only_on_chain, vec!(bob));
+        assert_eq!(diff.only_external, vec!(dave, erin));
+    }
+
+    #[test]
+    fn test_event_with_permissions() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone())));
+
+        assert_eq!(contract.get_event_with_permissions(alice.clone(), alice.clone()).viewer_role, ViewerRole::Owner);
+        assert_eq!(contract.get_event_with_permissions(alice.clone(), bob).viewer_role, ViewerRole::Guest);
+        let permissions = contract.get_event_with_permissions(alice, account("carol.testnet"));
+        assert_eq!(permissions.viewer_role, ViewerRole::None);
+        let json = near_sdk::serde_json::to_value(permissions).unwrap();
+        assert_eq!(json["viewerRole"], "none");
+        assert_eq!(json["event"]["price"], "10");
+    }
+
+    #[test]
+    fn test_analytics_metrics() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone())));
+        assert!(!near_sdk::test_utils::get_logs().iter().any(|log| log.contains("\"event\":\"metric\"")));
+
+        set_predecessor(&env::current_account_id());
+        contract.set_analytics_enabled(true);
+        assert!(contract.is_analytics_enabled());
+
+        set_predecessor(&alice);
+        contract.set_guests(vec!(account("carol.testnet")));
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(bob)) }, 0, 10);
+        let metrics: Vec<near_sdk::serde_json::Value> = near_sdk::test_utils::get_logs().iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|log| near_sdk::serde_json::from_str::<near_sdk::serde_json::Value>(log).unwrap())
+            .filter(|log| log["event"] == "metric")
+            .map(|log| log["data"][0].clone())
+            .collect();
+        assert_eq!(metrics, vec!(
+            json!({"metric": "join", "event_id": "alice.testnet", "amount": "10"}),
+            json!({"metric": "leave", "event_id": "alice.testnet", "amount": "10"}),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ADMIN")]
+    fn test_analytics_not_admin() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.set_analytics_enabled(true);
+    }
+
+    // what a guest submits for an email or phone field, hashed with the contact salt `salt`
+    fn signup_contact_hash(answer: &str) -> String {
+        env::sha256(format!("salt{}", answer).as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn signup_form() -> Vec<SignupField> {
+        vec!(
+            SignupField { label: "name".to_string(), field_type: FieldType::Text, required: true },
+            SignupField { label: "email".to_string(), field_type: FieldType::Email, required: true },
+            SignupField {
+                label: "diet".to_string(),
+                field_type: FieldType::SelectOne(vec!("vegan".to_string(), "any".to_string())),
+                required: false,
+            },
+        )
+    }
+
+    #[test]
+    fn test_signup_form() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_contact_salt(Base64VecU8::from(b"salt".to_vec()));
+        contract.set_signup_form(signup_form());
+        assert_eq!(contract.get_signup_form(alice.clone()).len(), 3);
+
+        set_predecessor(&bob);
+        let responses = vec!(
+            ("name".to_string(), "Bob".to_string()),
+            ("email".to_string(), signup_contact_hash("bob@example.com")),
+            ("diet".to_string(), "vegan".to_string()),
+        );
+        contract.submit_signup_form(alice.clone(), responses.clone());
+        assert_eq!(contract.get_event(alice.clone()).guests, vec!(bob.clone()));
+        assert_eq!(contract.get_signup_response(alice.clone(), bob.clone()), Some(responses));
+        assert_eq!(contract.get_guest_provenance(alice.clone(), bob.clone()).unwrap().via, JoinMethod::SignupForm);
+
+        // removing the guest removes the answers
+        set_predecessor(&alice);
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(bob.clone())) }, 0, 10);
+        assert!(contract.get_signup_response(alice, bob).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_MISSING_REQUIRED_FIELD")]
+    fn test_signup_form_missing_required_field() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_contact_salt(Base64VecU8::from(b"salt".to_vec()));
+        contract.set_signup_form(signup_form());
+
+        set_predecessor(&account("bob.testnet"));
+        contract.submit_signup_form(alice, vec!(("name".to_string(), "Bob".to_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_CONTACT_HASH")]
+    fn test_signup_form_raw_email() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_contact_salt(Base64VecU8::from(b"salt".to_vec()));
+        contract.set_signup_form(signup_form());
+
+        set_predecessor(&account("bob.testnet"));
+        contract.submit_signup_form(alice, vec!(
+            ("name".to_string(), "Bob".to_string()),
+            ("email".to_string(), "bob@example.com".to_string()),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_CONTACT_SALT_NOT_SET")]
+    fn test_signup_form_contact_field_without_salt() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_signup_form(signup_form());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_OPTION")]
+    fn test_signup_form_invalid_option() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_contact_salt(Base64VecU8::from(b"salt".to_vec()));
+        contract.set_signup_form(signup_form());
+
+        set_predecessor(&account("bob.testnet"));
+        contract.submit_signup_form(alice, vec!(
+            ("name".to_string(), "Bob".to_string()),
+            ("email".to_string(), signup_contact_hash("bob@example.com")),
+            ("diet".to_string(), "meat".to_string()),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SIGNUP_OPTIONS")]
+    fn test_signup_form_too_many_options() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_signup_form(vec!(SignupField {
+            label: "size".to_string(),
+            field_type: FieldType::SelectOne((0..21).map(|i| format!("option{}", i)).collect()),
+            required: true,
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SIGNUP_OPTIONS")]
+    fn test_signup_form_option_too_long() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_signup_form(vec!(SignupField {
+            label: "size".to_string(),
+            field_type: FieldType::SelectOne(vec!("x".repeat(65))),
+            required: true,
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_SIGNUP_FORM")]
+    fn test_signup_without_form() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        set_predecessor(&account("bob.testnet"));
+        contract.submit_signup_form(alice, vec!());
+    }
+
+    #[test]
+    fn test_reserved_blocks() {
+        let mut contract = Contract::default();
+        let (alice, sponsor) = (account("alice.testnet"), account("sponsor.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.reserve_block(alice.clone(), sponsor.clone(), 1);
+        contract.reserve_block(alice.clone(), sponsor.clone(), 1);
+        assert_eq!(contract.reserved_remaining(alice.clone(), sponsor.clone()), 2);
+
+        set_predecessor(&sponsor);
+        contract.assign_reserved(alice.clone(), account("bob.testnet"));
+        contract.assign_reserved(alice.clone(), account("carol.testnet"));
+        assert_eq!(contract.reserved_remaining(alice.clone(), sponsor.clone()), 0);
+        assert_eq!(contract.get_event(alice.clone()).guests.len(), 2);
+        assert_eq!(contract.get_guest_provenance(alice, account("bob.testnet")).unwrap().added_by, sponsor);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_RESERVED_SEATS")]
+    fn test_assign_reserved_over_allotment() {
+        let mut contract = Contract::default();
+        let (alice, sponsor) = (account("alice.testnet"), account("sponsor.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.reserve_block(alice.clone(), sponsor.clone(), 1);
+
+        set_predecessor(&sponsor);
+        contract.assign_reserved(alice.clone(), account("bob.testnet"));
+        contract.assign_reserved(alice, account("carol.testnet"));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_EVENT_OWNER")]
+    fn test_reserve_block_not_owner() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        set_predecessor(&account("sponsor.testnet"));
+        contract.reserve_block(alice, account("sponsor.testnet"), 50);
+    }
+
+    #[test]
+    fn test_registration_log() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        set_context(&alice, 100);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+        set_context(&alice, 200);
+        contract.remove_guests_matching(None, GuestPatternJSON { suffix: None, prefix: None, exact: Some(vec!(bob.clone())) }, 0, 10);
+        set_context(&alice, 300);
+        contract.set_guests(vec!(bob.clone()));
+
+        let log: Vec<(AccountId, RegistrationAction, u64)> = contract.registration_log(alice.clone(), 0, 10)
+            .into_iter().map(|entry| (entry.account, entry.action, entry.timestamp.0)).collect();
+        assert_eq!(log, vec!(
+            (bob.clone(), RegistrationAction::Joined, 100),
+            (carol, RegistrationAction::Joined, 100),
+            (bob.clone(), RegistrationAction::Left, 200),
+            (bob, RegistrationAction::Joined, 300),
+        ));
+        assert_eq!(contract.registration_log(alice.clone(), 3, 10).len(), 1);
+        assert_eq!(contract.registration_log(alice, 0, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_registration_log_after_reinsert() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        set_context(&alice, 100);
+        contract.insert_event(event_json(10, vec!(bob.clone(), carol.clone())));
+        set_context(&alice, 200);
+        contract.insert_event(event_json(10, vec!(carol.clone())));
+
+        let log: Vec<(AccountId, RegistrationAction, u64)> = contract.registration_log(alice, 0, 10)
+            .into_iter().map(|entry| (entry.account, entry.action, entry.timestamp.0)).collect();
+        assert_eq!(log, vec!(
+            (bob.clone(), RegistrationAction::Joined, 100),
+            (carol, RegistrationAction::Joined, 100),
+            (bob, RegistrationAction::Left, 200),
+        ));
+    }
+
+    #[test]
+    fn test_events_with_active_promotions() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        set_predecessor(&bob);
+        contract.insert_event(event_json(10, vec!()));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        assert!(contract.get_events_with_active_promotions(0, 10).is_empty());
+
+        contract.create_promo_code("VIP".to_string(), None, 1);
+        contract.create_promo_code("SPONSOR".to_string(), None, 2);
+        // replacing a code doesn't count it twice
+        contract.create_promo_code("SPONSOR".to_string(), None, 1);
+        let promotions = contract.get_events_with_active_promotions(0, 10);
+        assert_eq!(promotions.len(), 1);
+        assert_eq!((promotions[0].0.clone(), promotions[0].2), (alice.clone(), 2));
+
+        set_predecessor(&account("carol.testnet"));
+        contract.redeem_promo_code(alice.clone(), "VIP".to_string());
+        assert_eq!(contract.get_events_with_active_promotions(0, 10)[0].2, 1);
+        set_predecessor(&account("dave.testnet"));
+        contract.redeem_promo_code(alice, "SPONSOR".to_string());
+        assert!(contract.get_events_with_active_promotions(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_visibility() {
+        let mut contract = Contract::default();
+        let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+        set_context(&alice, 1);
+        contract.insert_event(event_json(10, vec!()));
+        contract.create_promo_code("VIP".to_string(), None, 1);
+        set_context(&bob, 1);
+        contract.insert_event(event_json(20, vec!(carol.clone())));
+        contract.set_slug("bob-party".to_string());
+        assert_eq!(contract.events_by_price_sorted(true, 0, 10).len(), 2);
+        assert_eq!(contract.get_events_with_active_promotions(0, 10).len(), 1);
+
+        // unlisted: gone from listings, readable directly
+        set_context(&alice, 1);
+        contract.set_visibility(Visibility::Unlisted);
+        assert!(contract.get_events_with_active_promotions(0, 10).is_empty());
+        assert!(contract.get_events_without_guests(None, 0, 10).is_empty());
+        assert_eq!(contract.events_by_price_sorted(true, 0, 10).len(), 1);
+        assert_eq!(contract.get_events_modified_after(U64::from(0), 10).len(), 2);
+        assert_eq!(contract.get_top_events_by_attendance(10).len(), 1);
+        assert_eq!(contract.get_event(alice.clone()).price.0, 10);
+
+        // private: also a stub in direct reads, except for the owner and the guests
+        set_context(&bob, 2);
+        contract.set_visibility(Visibility::Private);
+        let modified = contract.get_events_modified_after(U64::from(1), 10);
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].0, bob);
+        assert_eq!((modified[0].1.price.0, modified[0].1.guests.len(), modified[0].1.visibility), (0, 0, Visibility::Private));
+        let stub = contract.get_event(bob.clone());
+        assert_eq!((stub.price.0, stub.guests.len(), stub.visibility), (0, 0, Visibility::Private));
+        assert_eq!(contract.get_event_by_slug("bob-party".to_string()).unwrap().price.0, 0);
+        assert_eq!(contract.get_event_with_permissions(bob.clone(), carol).event.price.0, 20);
+        assert_eq!(contract.get_event_with_permissions(bob.clone(), account("dave.testnet")).event.price.0, 0);
+
+        // back to public
+        set_context(&alice, 1);
+        contract.set_visibility(Visibility::Public);
+        assert_eq!(contract.get_events_with_active_promotions(0, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_private_event_details() {
+        let mut contract = Contract::default();
+        let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(bob.clone())));
+        contract.set_event_localization("en".to_string(), EventLocalization {
+            title: "Secret party".to_string(),
+            description: "Welcome".to_string(),
+        });
+        contract.set_extension("app.discord.channel".to_string(), "123".to_string());
+        contract.set_description_chunk(0, "agenda".to_string());
+        contract.finalize_description(1, Base64VecU8::from(env::sha256(b"agenda")));
+        contract.set_visibility(Visibility::Private);
+
+        // hidden from anyone but the owner and the guests
+        for viewer in [None, Some(account("dave.testnet"))] {
+            assert!(contract.get_event_localized(alice.clone(), "en".to_string(), viewer.clone()).is_none());
+            assert!(contract.get_extension(alice.clone(), "app.discord.channel".to_string(), viewer.clone()).is_none());
+            assert!(contract.get_description(alice.clone(), 0, viewer).is_none());
+        }
+        for viewer in [alice.clone(), bob] {
+            assert_eq!(contract.get_event_localized(alice.clone(), "en".to_string(), Some(viewer.clone())).unwrap().title, "Secret party");
+            assert_eq!(contract.get_extension(alice.clone(), "app.discord.channel".to_string(), Some(viewer.clone())), Some("123".to_string()));
+            assert_eq!(contract.get_description(alice.clone(), 0, Some(viewer)), Some("agenda".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_lock_settings() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.lock_settings(alice.clone());
+        assert!(contract.get_event(alice.clone()).settings_locked);
+
+        // guests are still managed with their own methods
+        contract.set_guests(vec!(account("bob.testnet"), account("carol.testnet")));
+        let event = contract.get_event(alice);
+        assert!(event.settings_locked);
+        assert_eq!(event.guests.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_SETTINGS_LOCKED")]
+    fn test_locked_price_change() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        contract.lock_settings(alice);
+        contract.insert_event(event_json(20, vec!()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_SETTINGS_LOCKED")]
+    fn test_locked_reinsert() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!(account("bob.testnet"))));
+        contract.lock_settings(alice);
+        // would drop bob even at the same price
+        contract.insert_event(event_json(10, vec!()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_EVENT_OWNER")]
+    fn test_lock_settings_not_owner() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+        set_predecessor(&account("bob.testnet"));
+        contract.lock_settings(alice);
+    }
+
+    #[test]
+    fn test_chunked_description() {
+        let mut contract = Contract::default();
+        let alice = account("alice.testnet");
+        set_predecessor(&alice);
+        contract.insert_event(event_json(10, vec!()));
+
+        // chunks can be uploaded in any order
+        contract.set_description_chunk(2, "three".to_string());
+        contract.set_description_chunk(0, "one ".to_string());
+        contract.set_description_chunk(1, "two ".to_string());
+        assert!(contract.get_description(alice.clone(), 0, None).is_none());
+        let hash = env::sha256(b"one two three");
+        contract.finalize_description(3, Base64VecU8::from(hash.clone()));
+        assert_eq!(contract.get_description(alice.clone(), 1, None), Some("two ".to_string()));
+        assert_eq!(contract.get_event(alice.clone()).description_hash.unwrap().0, hash);
+
+        // a shorter description removes the stale chunks
+        contract.set_description_chunk(0, "short".to_string());
+        assert!(contract.get_event(alice.clone()).description_hash.is_none());
+        contract.finalize_description(1, Base64VecU8::from(env::sha256(b"short")));
+        assert_eq!(contract.get_description(alice.clone(), 0, None), Some("short".to_string()));
+        assert!(contract.get_description(alice, 1, None).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DESCRIPTION_HASH_MISMATCH")]
+    fn test_description_hash_mismatch() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_description_chunk(0, "agenda".to_string());
+        contract.finalize_description(1, Base64VecU8::from(env::sha256(b"another agenda")));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_MISSING_DESCRIPTION_CHUNK")]
+    fn test_description_stale_chunks_removed() {
+        let mut contract = Contract::default();
+        set_predecessor(&account("alice.testnet"));
+        contract.insert_event(event_json(10, vec!()));
+        contract.set_description_chunk(0, "one ".to_string());
+        contract.set_description_chunk(1, "two".to_string());
+        contract.finalize_description(1, Base64VecU8::from(env::sha256(b"one ")));
+        // chunk 1 was removed by the shorter description
+        contract.set_description_chunk(0, "one ".to_string());
+        contract.finalize_description(2, Base64VecU8::from(env::sha256(b"one two")));
+    }
 }
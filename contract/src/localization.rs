@@ -0,0 +1,54 @@
+use crate::*;
+
+// Localized title and description per language code, e.g. "en", "fr" or "pt-BR"
+const MAX_LOCALIZATIONS: u64 = 20;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventLocalization {
+    pub title: String,
+    pub description: String,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn set_event_localization(&mut self, language_code: String, localization: EventLocalization) {
+        let initial_storage_usage = env::storage_usage();
+        assert_valid_language_code(&language_code);
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        let is_new_language = event.localizations.insert(&language_code, &localization).is_none();
+        assert!(!is_new_language || event.localizations.len() <= MAX_LOCALIZATIONS, "ERR_TOO_MANY_LOCALIZATIONS");
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // None for private events unless `viewer` is the owner or a guest, `viewer` isn't verified
+    pub fn get_event_localized(
+        &self,
+        event_owner_id: EventOwnerId,
+        language_code: String,
+        viewer: Option<AccountId>,
+    ) -> Option<EventLocalization> {
+        let event = self.internal_get_event(&event_owner_id);
+        if !event.is_visible_to(viewer.as_ref()) {
+            return None;
+        }
+        event.localizations.get(&language_code)
+    }
+
+    pub fn get_available_languages(&self, event_owner_id: EventOwnerId) -> Vec<String> {
+        self.internal_get_event(&event_owner_id).localizations.keys().collect()
+    }
+}
+
+// 2-5 ASCII letters, digits or dashes
+pub(crate) fn assert_valid_language_code(language_code: &str) {
+    assert!(
+        (2..=5).contains(&language_code.len())
+            && language_code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+        "ERR_INVALID_LANGUAGE_CODE"
+    );
+}
@@ -0,0 +1,14 @@
+use crate::*;
+
+// Assurance for guests: once the settings are locked the organizer can't change the price anymore.
+// The lock is irreversible. `insert_event` is refused as a whole, so a re-insert can't drop guests
+// either. Guests and all other event data can still be managed with their own methods.
+#[near_bindgen]
+impl Contract {
+    pub fn lock_settings(&mut self, event_owner_id: EventOwnerId) {
+        let event_owner_id = self.assert_event_owner(Some(event_owner_id));
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.settings_locked = true;
+        self.internal_set_event(&event_owner_id, &mut event);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::*;
+
+// NEP-297 event logs: https://nomicon.io/Standards/EventsFormat
+const EVENT_STANDARD: &str = "near_serialize";
+const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+pub(crate) fn emit_event<T: Serialize>(event: &str, data: T) {
+    let log = near_sdk::serde_json::json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_STANDARD_VERSION,
+        "event": event,
+        "data": [data],
+    });
+    env::log_str(&format!("EVENT_JSON:{}", log));
+}
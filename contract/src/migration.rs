@@ -0,0 +1,166 @@
+use crate::*;
+use crate::localization::EventLocalization;
+use crate::milestones::EventMilestone;
+use crate::promo::PromoCode;
+
+// Converts the state of an older deployment to the current layout. Deploy the new code with
+// `upgrade`, then call `migrate` as the contract account. Older layouts are kept below exactly as
+// they were stored, they are only ever read.
+
+// Before the state was versioned: events in a LookupMap, so they can't be enumerated and the admin
+// passes the owner ids of all events (e.g. from an indexer). Events left out stay unreachable in
+// the old map.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV0 {
+    pub events: LookupMap<EventOwnerId, EventV0>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct EventV0 {
+    pub price: u128,
+    pub guests: UnorderedSet<AccountId>,
+}
+
+// The fields every versioned layout starts with, up to the stored version. Collections are read as
+// their storage prefixes only, so the value types don't matter.
+#[derive(BorshDeserialize)]
+struct StateHeader {
+    _events: UnorderedMap<EventOwnerId, ()>,
+    _slugs: UnorderedMap<String, EventOwnerId>,
+    _unique_guests: UnorderedSet<AccountId>,
+    _verified_organizers: UnorderedSet<AccountId>,
+    _webhooks: UnorderedMap<AccountId, ()>,
+    _code_hash: Option<Vec<u8>>,
+    version: u16,
+}
+
+// Version 1, every event is rewritten in a single call
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV1 {
+    pub events: UnorderedMap<EventOwnerId, EventV1>,
+    pub slugs: UnorderedMap<String, EventOwnerId>,
+    pub unique_guests: UnorderedSet<AccountId>,
+    pub verified_organizers: UnorderedSet<AccountId>,
+    pub webhooks: UnorderedMap<AccountId, Vec<WebhookRegistration>>,
+    pub code_hash: Option<Vec<u8>>,
+    pub version: u16,
+    pub events_by_modification_time: TreeMap<u64, UnorderedSet<EventOwnerId>>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct EventV1 {
+    pub owner_id: EventOwnerId,
+    pub price: u128,
+    pub guests: UnorderedSet<AccountId>,
+    pub slug: Option<String>,
+    pub guest_sweep_cursor: u64,
+    pub milestones: Vec<EventMilestone>,
+    pub preauthorized: LookupSet<AccountId>,
+    pub contact_salt: Option<Vec<u8>>,
+    pub contact_hashes: UnorderedMap<AccountId, [u8; 32]>,
+    pub extensions: UnorderedMap<String, String>,
+    pub last_modified_at: u64,
+    pub invites: UnorderedSet<Vec<u8>>,
+    pub redeemed_invites: LookupSet<Vec<u8>>,
+    pub invite_nonce: u64,
+    pub localizations: UnorderedMap<String, EventLocalization>,
+    pub display_decimals: u8,
+    pub promo_codes: UnorderedMap<String, PromoCodeV1>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct PromoCodeV1 {
+    pub recipient: Option<AccountId>,
+    pub uses_remaining: u32,
+    pub is_complimentary: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Guests of migrated events keep their place in the list but get no ticket number, provenance
+    // or registration log entry, those didn't exist when they joined.
+    #[init(ignore_state)]
+    pub fn migrate(event_owner_ids: Vec<EventOwnerId>) -> Self {
+        assert_admin();
+        let state = env::storage_read(b"STATE").expect("ERR_CONTRACT_NOT_INITIALIZED");
+        // the state from before versioning is a single storage prefix, too short for the header
+        let version = match StateHeader::deserialize(&mut state.as_slice()) {
+            Ok(header) => header.version,
+            Err(_) => 0,
+        };
+        match version {
+            0 => {
+                let old_state = ContractV0::try_from_slice(&state).expect("ERR_UNKNOWN_STATE_LAYOUT");
+                // the initialization block is unknown, `created_at_block` is the block of the migration
+                let mut contract = Contract::default();
+                contract.internal_migrate_events_v0(old_state, event_owner_ids);
+                contract
+            }
+            1 => Contract::internal_migrate_v1(ContractV1::try_from_slice(&state).expect("ERR_UNKNOWN_STATE_LAYOUT")),
+            STATE_VERSION => panic!("ERR_ALREADY_MIGRATED"),
+            version => panic!("ERR_UNKNOWN_STATE_VERSION: {}", version),
+        }
+    }
+
+    // Maps with a changed value type are read out, cleared and filled again: inserting over an old
+    // entry would deserialize it as the new type.
+    fn internal_migrate_v1(mut old_state: ContractV1) -> Self {
+        let old_events = old_state.events.to_vec();
+        old_state.events.clear();
+        let mut contract = Contract {
+            slugs: old_state.slugs,
+            unique_guests: old_state.unique_guests,
+            verified_organizers: old_state.verified_organizers,
+            webhooks: old_state.webhooks,
+            code_hash: old_state.code_hash,
+            events_by_modification_time: old_state.events_by_modification_time,
+            ..Contract::default()
+        };
+        for (event_owner_id, old_event) in old_events {
+            let mut event = Event::new(&event_owner_id, old_event.price);
+            event.guests = old_event.guests;
+            event.slug = old_event.slug;
+            event.milestones = old_event.milestones;
+            event.preauthorized = old_event.preauthorized;
+            event.contact_salt = old_event.contact_salt;
+            event.contact_hashes = old_event.contact_hashes;
+            event.extensions = old_event.extensions;
+            event.last_modified_at = old_event.last_modified_at;
+            event.invites = old_event.invites;
+            event.redeemed_invites = old_event.redeemed_invites;
+            event.localizations = old_event.localizations;
+            event.display_decimals = old_event.display_decimals;
+
+            let mut old_promo_codes = old_event.promo_codes;
+            let promo_codes = old_promo_codes.to_vec();
+            old_promo_codes.clear();
+            for (code, promo_code) in promo_codes {
+                if promo_code.uses_remaining > 0 {
+                    event.active_promo_codes += 1;
+                }
+                event.promo_codes.insert(&code, &PromoCode {
+                    recipient: promo_code.recipient,
+                    uses_remaining: promo_code.uses_remaining,
+                });
+            }
+
+            // not `internal_set_event`, the event keeps its modification time
+            contract.internal_index_promotions(&event_owner_id, &event);
+            contract.events.insert(&event_owner_id, &event);
+        }
+        contract
+    }
+
+    fn internal_migrate_events_v0(&mut self, mut old_state: ContractV0, event_owner_ids: Vec<EventOwnerId>) {
+        for event_owner_id in event_owner_ids {
+            let old_event = old_state.events.remove(&event_owner_id).expect("ERR_MISSING_EVENT");
+            let mut event = Event::new(&event_owner_id, old_event.price);
+            // same storage prefix, the guests stay where they are
+            event.guests = old_event.guests;
+            for guest in event.guests.iter() {
+                self.unique_guests.insert(&guest);
+            }
+            self.internal_set_event(&event_owner_id, &mut event);
+        }
+    }
+}
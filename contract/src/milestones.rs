@@ -0,0 +1,59 @@
+use crate::*;
+use near_sdk::json_types::U64;
+
+// Organizer task reminders attached to an event, e.g. "book the venue" due at a given timestamp
+const MAX_MILESTONES: usize = 20;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct EventMilestone {
+    pub label: String,
+    // nanoseconds, same unit as env::block_timestamp(), a string in JSON so JavaScript clients don't
+    // lose precision
+    pub due_at: U64,
+    pub completed: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn add_event_milestone(&mut self, label: String, due_at: U64) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        assert!(event.milestones.len() < MAX_MILESTONES, "ERR_TOO_MANY_MILESTONES");
+
+        event.milestones.push(EventMilestone { label, due_at, completed: false });
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn complete_milestone(&mut self, milestone_index: u32) {
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        let milestone = event.milestones.get_mut(milestone_index as usize).expect("ERR_MISSING_MILESTONE");
+        milestone.completed = true;
+        self.internal_set_event(&event_owner_id, &mut event);
+    }
+
+    // Not completed milestones due before now + `within_seconds`, overdue ones included.
+    // Every organizer owns a single event, so the event reference is the organizer account id.
+    pub fn get_events_with_upcoming_milestones(
+        &self,
+        organizer: AccountId,
+        within_seconds: u64,
+    ) -> Vec<(String, EventMilestone)> {
+        let deadline = env::block_timestamp().saturating_add(within_seconds.saturating_mul(1_000_000_000));
+        match self.events.get(&organizer) {
+            Some(event) => event
+                .milestones
+                .into_iter()
+                .filter(|milestone| !milestone.completed && milestone.due_at.0 <= deadline)
+                .map(|milestone| (organizer.to_string(), milestone))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
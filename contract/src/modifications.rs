@@ -0,0 +1,45 @@
+use crate::*;
+use near_sdk::json_types::U64;
+use std::ops::Bound;
+
+// Incremental cache invalidation: every write stamps the event with the block timestamp and
+// indexes its owner by that time, so clients can fetch only the events changed since their last
+// sync.
+#[near_bindgen]
+impl Contract {
+    // Events modified strictly after `timestamp` (nanoseconds, a string in JSON so JavaScript
+    // clients don't lose precision), oldest modification first. Unlisted and private events are
+    // kept, so caches also hear about an event leaving the public listings, private ones as a stub
+    // that replaces the cached copy.
+    pub fn get_events_modified_after(&self, timestamp: U64, limit: u64) -> Vec<(EventOwnerId, EventJSON)> {
+        self.events_by_modification_time
+            .range((Bound::Excluded(timestamp.0), Bound::Unbounded))
+            .flat_map(|(_, event_owner_ids)| event_owner_ids.to_vec())
+            .take(limit as usize)
+            .map(|event_owner_id| {
+                let event = self.internal_get_event(&event_owner_id);
+                (event_owner_id, event.to_visible_json(self))
+            })
+            .collect()
+    }
+
+    // move the event in the modification index from its previous timestamp to now
+    pub(crate) fn internal_touch_event(&mut self, event_owner_id: &EventOwnerId, event: &mut Event) {
+        let now = env::block_timestamp();
+        if let Some(mut event_owner_ids) = self.events_by_modification_time.get(&event.last_modified_at) {
+            event_owner_ids.remove(event_owner_id);
+            if event_owner_ids.is_empty() {
+                self.events_by_modification_time.remove(&event.last_modified_at);
+            } else {
+                self.events_by_modification_time.insert(&event.last_modified_at, &event_owner_ids);
+            }
+        }
+
+        let mut event_owner_ids = self.events_by_modification_time
+            .get(&now)
+            .unwrap_or_else(|| UnorderedSet::new(StorageKey::ModifiedEvents { timestamp: now }));
+        event_owner_ids.insert(event_owner_id);
+        self.events_by_modification_time.insert(&now, &event_owner_ids);
+        event.last_modified_at = now;
+    }
+}
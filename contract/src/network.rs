@@ -0,0 +1,22 @@
+use crate::*;
+
+// Summary of this instance for a hub contract that aggregates several deployments
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkContribution {
+    pub contract_id: AccountId,
+    pub total_events: u64,
+    pub active_since_block: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_network_contribution(&self) -> NetworkContribution {
+        NetworkContribution {
+            contract_id: env::current_account_id(),
+            total_events: self.events.len(),
+            active_since_block: self.created_at_block,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::*;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ViewerRole {
+    Owner,
+    Guest,
+    None,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct EventWithPermissions {
+    pub event: EventJSON,
+    pub viewer_role: ViewerRole,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Views have no caller, so the frontend passes the signed-in account as `viewer`. It isn't
+    // verified: passing the owner's account id returns the full event, private or not
+    pub fn get_event_with_permissions(&self, event_owner_id: EventOwnerId, viewer: AccountId) -> EventWithPermissions {
+        let event = self.internal_get_event(&event_owner_id);
+        let viewer_role = event.viewer_role(&viewer);
+        let event = if viewer_role == ViewerRole::None { event.to_visible_json(self) } else { event.to_json(self) };
+        EventWithPermissions { event, viewer_role }
+    }
+}
+
+impl Event {
+    pub fn viewer_role(&self, viewer: &AccountId) -> ViewerRole {
+        if &self.owner_id == viewer {
+            ViewerRole::Owner
+        } else if self.guests.contains(viewer) {
+            ViewerRole::Guest
+        } else {
+            ViewerRole::None
+        }
+    }
+}
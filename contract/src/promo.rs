@@ -0,0 +1,78 @@
+use crate::*;
+
+// Promo codes granting free access, e.g. for sponsors. A code can be bound to a single recipient
+// or left open for anyone, and can be redeemed `uses` times.
+const MAX_PROMO_CODE_LENGTH: usize = 32;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct PromoCode {
+    pub recipient: Option<AccountId>,
+    pub uses_remaining: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn create_promo_code(&mut self, code: String, recipient: Option<AccountId>, uses: u32) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(!code.is_empty() && code.len() <= MAX_PROMO_CODE_LENGTH, "ERR_INVALID_PROMO_CODE");
+        assert!(uses > 0, "ERR_INVALID_PROMO_CODE_USES");
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+
+        let old_promo_code = event.promo_codes.insert(&code, &PromoCode { recipient, uses_remaining: uses });
+        if old_promo_code.is_none_or(|old_promo_code| old_promo_code.uses_remaining == 0) {
+            event.active_promo_codes += 1;
+        }
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // joins the caller to the event for free
+    #[payable]
+    pub fn redeem_promo_code(&mut self, event_owner_id: EventOwnerId, code: String) {
+        let initial_storage_usage = env::storage_usage();
+        let guest = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        let mut promo_code = event.promo_codes.get(&code).expect("ERR_INVALID_PROMO_CODE");
+        assert!(promo_code.uses_remaining > 0, "ERR_PROMO_CODE_EXHAUSTED");
+        if let Some(recipient) = &promo_code.recipient {
+            assert_eq!(recipient, &guest, "ERR_PROMO_CODE_WRONG_RECIPIENT");
+        }
+        assert!(!event.guests.contains(&guest), "ERR_ALREADY_A_GUEST");
+
+        promo_code.uses_remaining -= 1;
+        if promo_code.uses_remaining == 0 {
+            event.active_promo_codes -= 1;
+        }
+        event.promo_codes.insert(&code, &promo_code);
+        self.internal_add_guest(&event_owner_id, &mut event, &guest, JoinMethod::PromoCode);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // Events with codes that can still be redeemed, with the number of such codes
+    pub fn get_events_with_active_promotions(&self, from_index: u64, limit: u64) -> Vec<(EventOwnerId, EventJSON, u32)> {
+        self.events_with_active_promotions
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|event_owner_id| {
+                let event = self.internal_get_event(&event_owner_id);
+                let active_promo_codes = event.active_promo_codes;
+                (event_owner_id, event.to_json(self), active_promo_codes)
+            })
+            .collect()
+    }
+
+    // keep the discovery index in sync with the event's active codes and visibility
+    pub(crate) fn internal_index_promotions(&mut self, event_owner_id: &EventOwnerId, event: &Event) {
+        if event.active_promo_codes > 0 && event.is_listed() {
+            self.events_with_active_promotions.insert(event_owner_id);
+        } else {
+            self.events_with_active_promotions.remove(event_owner_id);
+        }
+    }
+}
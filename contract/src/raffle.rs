@@ -0,0 +1,52 @@
+use crate::*;
+use std::collections::HashMap;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct RaffleLog<'a> {
+    event_owner_id: &'a EventOwnerId,
+    winners: &'a Vec<AccountId>,
+}
+
+// On-chain giveaways. The draw uses `env::random_seed()`, which is fine for small prizes but not
+// for valuable ones: the seed is the same for every call in the block and block producers can
+// influence it, e.g. by skipping a block. The result is stored once, so it can't be re-rolled.
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn draw_winners(&mut self, event_owner_id: EventOwnerId, count: u64) -> Vec<AccountId> {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = self.assert_event_owner(Some(event_owner_id));
+        let mut event = self.internal_get_event(&event_owner_id);
+        assert!(event.raffle_winners.is_none(), "ERR_WINNERS_ALREADY_DRAWN");
+        assert!(count > 0, "ERR_ZERO_WINNERS");
+        assert!(count <= event.guests.len(), "ERR_NOT_ENOUGH_GUESTS");
+
+        // Partial Fisher-Yates shuffle over guest indices, a fresh hash of the seed for every pick.
+        // Only the swapped positions are kept, so the guests are never loaded as a whole and the
+        // cost grows with `count`, not with the event. The counter is a u64 to hash the same bytes
+        // on wasm32 and in native tests.
+        let random_seed = env::random_seed();
+        let guests_len = event.guests.len();
+        let mut swapped: HashMap<u64, u64> = HashMap::new();
+        let mut winners = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let hash = env::sha256(&[random_seed.as_slice(), &i.to_le_bytes()].concat());
+            let random = u64::from_le_bytes(hash[..8].try_into().unwrap());
+            let j = i + random % (guests_len - i);
+            let picked = swapped.get(&j).copied().unwrap_or(j);
+            swapped.insert(j, swapped.get(&i).copied().unwrap_or(i));
+            winners.push(event.guests.as_vector().get(picked).unwrap());
+        }
+
+        emit_event("raffle_winners", RaffleLog { event_owner_id: &event_owner_id, winners: &winners });
+        event.raffle_winners = Some(winners.clone());
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+        winners
+    }
+
+    pub fn get_raffle_winners(&self, event_owner_id: EventOwnerId) -> Option<Vec<AccountId>> {
+        self.internal_get_event(&event_owner_id).raffle_winners
+    }
+}
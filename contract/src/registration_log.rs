@@ -0,0 +1,45 @@
+use crate::*;
+use near_sdk::json_types::U64;
+
+// Append-only on-chain history of joins and departures per event. Removing a guest appends a
+// `left` entry instead of rewriting history.
+const MAX_REGISTRATION_LOG_PAGE: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationAction {
+    Joined,
+    Left,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub account: AccountId,
+    pub action: RegistrationAction,
+    // block timestamp in nanoseconds
+    pub timestamp: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    // oldest first, pages hold at most 100 entries
+    pub fn registration_log(&self, event_owner_id: EventOwnerId, from_index: u64, limit: u64) -> Vec<LogEntry> {
+        self.internal_get_event(&event_owner_id)
+            .registration_log
+            .iter()
+            .skip(from_index as usize)
+            .take(limit.min(MAX_REGISTRATION_LOG_PAGE) as usize)
+            .collect()
+    }
+}
+
+pub(crate) fn internal_log_registration(event: &mut Event, account: &AccountId, action: RegistrationAction) {
+    event.registration_log.push(&LogEntry {
+        account: account.clone(),
+        action,
+        timestamp: U64::from(env::block_timestamp()),
+    });
+}
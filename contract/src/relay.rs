@@ -0,0 +1,33 @@
+use crate::*;
+
+// Gasless onboarding: the organizer pre-approves an account, then a relayer pays the gas to
+// register it. The relayer can only register accounts the organizer approved, and every approval
+// can be used once.
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn preauthorize(&mut self, event_owner_id: EventOwnerId, account: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = self.assert_event_owner(Some(event_owner_id));
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.preauthorized.insert(&account);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // called by a relayer, registers `account` rather than the predecessor. The relayer also
+    // covers the storage of the new guest.
+    #[payable]
+    pub fn relayed_join(&mut self, event_owner_id: EventOwnerId, account: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let mut event = self.internal_get_event(&event_owner_id);
+        // checked first, so the approval isn't used up by an account that can't join
+        if let Err(status) = check_guest(&event, account.as_str()) {
+            panic!("{}", status.error_code());
+        }
+        assert!(event.preauthorized.remove(&account), "ERR_NOT_PREAUTHORIZED");
+        self.internal_add_guest(&event_owner_id, &mut event, &account, JoinMethod::Relayed);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+
+// Seats set aside for allocation partners, e.g. a sponsor with 50 seats to hand out. The partner
+// adds guests to the event until their allotment runs out.
+#[near_bindgen]
+impl Contract {
+    // adds `count` seats to the partner's allotment
+    #[payable]
+    pub fn reserve_block(&mut self, event_owner_id: EventOwnerId, partner: AccountId, count: u32) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = self.assert_event_owner(Some(event_owner_id));
+        assert!(count > 0, "ERR_ZERO_RESERVED_SEATS");
+        let mut event = self.internal_get_event(&event_owner_id);
+        let remaining = event.reserved_blocks.get(&partner).unwrap_or(0);
+        event.reserved_blocks.insert(&partner, &remaining.checked_add(count).expect("ERR_TOO_MANY_RESERVED_SEATS"));
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // called by the partner
+    #[payable]
+    pub fn assign_reserved(&mut self, event_owner_id: EventOwnerId, guest: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let partner = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        let remaining = event.reserved_blocks.get(&partner).unwrap_or(0);
+        assert!(remaining > 0, "ERR_NO_RESERVED_SEATS");
+        if let Err(status) = check_guest(&event, guest.as_str()) {
+            panic!("{}", status.error_code());
+        }
+
+        self.internal_add_guest(&event_owner_id, &mut event, &guest, JoinMethod::Reserved);
+        if remaining == 1 {
+            event.reserved_blocks.remove(&partner);
+        } else {
+            event.reserved_blocks.insert(&partner, &(remaining - 1));
+        }
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn reserved_remaining(&self, event_owner_id: EventOwnerId, partner: AccountId) -> u32 {
+        self.internal_get_event(&event_owner_id).reserved_blocks.get(&partner).unwrap_or(0)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::*;
+
+// Guests join some events by filling in a form declared by the organizer. Responses are public
+// on-chain state like everything else, and so are the arguments of the call that submits them.
+// Email and phone answers are therefore never sent in the clear: like contacts.rs, the guest submits
+// the hex `sha256(salt + answer)` with the event's contact salt and the organizer matches it
+// against their own list. Text answers are stored as given, forms shouldn't ask for anything
+// sensitive in them.
+const MAX_SIGNUP_FIELDS: usize = 10;
+const MAX_SIGNUP_LABEL_LENGTH: usize = 64;
+const MAX_SIGNUP_RESPONSE_LENGTH: usize = 256;
+const MAX_SIGNUP_OPTIONS: usize = 20;
+const MAX_SIGNUP_OPTION_LENGTH: usize = 64;
+const SIGNUP_CONTACT_HASH_HEX_LENGTH: usize = 64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Text,
+    Email,
+    Phone,
+    SelectOne(Vec<String>),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct SignupField {
+    pub label: String,
+    pub field_type: FieldType,
+    pub required: bool,
+}
+
+impl SignupField {
+    fn assert_valid_response(&self, response: &str) {
+        assert!(response.len() <= MAX_SIGNUP_RESPONSE_LENGTH, "ERR_SIGNUP_RESPONSE_TOO_LONG");
+        match &self.field_type {
+            FieldType::Text => {}
+            FieldType::Email | FieldType::Phone => assert!(
+                response.len() == SIGNUP_CONTACT_HASH_HEX_LENGTH
+                    && response.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+                "ERR_INVALID_CONTACT_HASH"
+            ),
+            FieldType::SelectOne(options) => assert!(options.iter().any(|option| option == response), "ERR_INVALID_OPTION"),
+        }
+    }
+
+    fn is_contact(&self) -> bool {
+        matches!(self.field_type, FieldType::Email | FieldType::Phone)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // an empty list removes the form and closes signups
+    #[payable]
+    pub fn set_signup_form(&mut self, fields: Vec<SignupField>) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        assert!(fields.len() <= MAX_SIGNUP_FIELDS, "ERR_TOO_MANY_SIGNUP_FIELDS");
+        for (index, field) in fields.iter().enumerate() {
+            assert!(!field.label.is_empty() && field.label.len() <= MAX_SIGNUP_LABEL_LENGTH, "ERR_INVALID_SIGNUP_LABEL");
+            assert!(fields[..index].iter().all(|other| other.label != field.label), "ERR_DUPLICATE_SIGNUP_LABEL");
+            if let FieldType::SelectOne(options) = &field.field_type {
+                assert!(!options.is_empty() && options.len() <= MAX_SIGNUP_OPTIONS, "ERR_INVALID_SIGNUP_OPTIONS");
+                assert!(
+                    options.iter().all(|option| !option.is_empty() && option.len() <= MAX_SIGNUP_OPTION_LENGTH),
+                    "ERR_INVALID_SIGNUP_OPTIONS"
+                );
+            }
+        }
+        // guests hash their contact answers with the salt
+        assert!(event.contact_salt.is_some() || !fields.iter().any(SignupField::is_contact), "ERR_CONTACT_SALT_NOT_SET");
+        event.signup_form_fields = fields;
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    // joins the event as the caller, `responses` are (label, answer) pairs
+    #[payable]
+    pub fn submit_signup_form(&mut self, event_owner_id: EventOwnerId, responses: Vec<(String, String)>) {
+        let initial_storage_usage = env::storage_usage();
+        let mut event = self.internal_get_event(&event_owner_id);
+        assert!(!event.signup_form_fields.is_empty(), "ERR_NO_SIGNUP_FORM");
+
+        for (index, (label, response)) in responses.iter().enumerate() {
+            let field = event.signup_form_fields.iter().find(|field| &field.label == label).expect("ERR_UNKNOWN_SIGNUP_FIELD");
+            assert!(responses[..index].iter().all(|(other, _)| other != label), "ERR_DUPLICATE_SIGNUP_LABEL");
+            field.assert_valid_response(response);
+        }
+        for field in event.signup_form_fields.iter().filter(|field| field.required) {
+            assert!(
+                responses.iter().any(|(label, response)| label == &field.label && !response.is_empty()),
+                "ERR_MISSING_REQUIRED_FIELD"
+            );
+        }
+
+        let guest = match check_guest(&event, env::predecessor_account_id().as_str()) {
+            Ok(guest) => guest,
+            Err(status) => panic!("{}", status.error_code()),
+        };
+        self.internal_add_guest(&event_owner_id, &mut event, &guest, JoinMethod::SignupForm);
+        event.signup_responses.insert(&guest, &responses);
+        self.internal_set_event(&event_owner_id, &mut event);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_signup_form(&self, event_owner_id: EventOwnerId) -> Vec<SignupField> {
+        self.internal_get_event(&event_owner_id).signup_form_fields
+    }
+
+    pub fn get_signup_response(&self, event_owner_id: EventOwnerId, account_id: AccountId) -> Option<Vec<(String, String)>> {
+        self.internal_get_event(&event_owner_id).signup_responses.get(&account_id)
+    }
+}
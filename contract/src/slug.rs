@@ -0,0 +1,47 @@
+use crate::*;
+
+// Organizers can share events by a human-readable slug (e.g. `/e/my-cool-event`) instead of the
+// owner account id. Slugs are stored lowercase and may only contain `a-z`, `0-9` and `-`.
+const MAX_SLUG_LENGTH: usize = 64;
+
+#[near_bindgen]
+impl Contract {
+    // attach a slug to the caller's event, replacing the previous one
+    #[payable]
+    pub fn set_slug(&mut self, slug: String) {
+        let initial_storage_usage = env::storage_usage();
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        let slug = normalize_slug(&slug);
+
+        match self.slugs.get(&slug) {
+            Some(owner) => assert_eq!(owner, event_owner_id, "ERR_SLUG_TAKEN"),
+            None => {
+                if let Some(old_slug) = event.slug.take() {
+                    self.slugs.remove(&old_slug);
+                }
+                self.slugs.insert(&slug, &event_owner_id);
+                event.slug = Some(slug);
+                self.internal_set_event(&event_owner_id, &mut event);
+            }
+        }
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_event_by_slug(&self, slug: String) -> Option<EventJSON> {
+        self.slugs
+            .get(&slug.to_lowercase())
+            .and_then(|event_owner_id| self.events.get(&event_owner_id))
+            .map(|event| event.to_visible_json(self))
+    }
+}
+
+pub(crate) fn normalize_slug(slug: &str) -> String {
+    let slug = slug.to_lowercase();
+    assert!(!slug.is_empty() && slug.len() <= MAX_SLUG_LENGTH, "ERR_INVALID_SLUG_LENGTH");
+    assert!(
+        slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+        "ERR_INVALID_SLUG"
+    );
+    slug
+}
@@ -0,0 +1,80 @@
+use crate::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Lower bounds of the guest count buckets: 0-10, 11-100, 101-1000, 1001+
+const GUEST_COUNT_BUCKETS: [u64; 4] = [0, 11, 101, 1001];
+const MAX_TOP_EVENTS: u64 = 20;
+// events loaded by `events_by_price_sorted` before sorting
+const MAX_PRICE_SORT_SCAN: usize = 1000;
+
+#[near_bindgen]
+impl Contract {
+    // Histogram of events by guest count as (bucket lower bound, number of events). Scans
+    // `limit` events starting at `from_index`, so dashboards page through all events and sum up
+    // the counts of every page instead of scanning the whole map in one call.
+    pub fn guest_count_buckets(&self, from_index: u64, limit: u64) -> Vec<(u64, u64)> {
+        let mut buckets: Vec<(u64, u64)> = GUEST_COUNT_BUCKETS.iter().map(|bound| (*bound, 0)).collect();
+        for (_, event) in self.events.iter().skip(from_index as usize).take(limit as usize) {
+            let guests_count = event.guests.len();
+            let bucket = buckets.iter_mut().rev().find(|(bound, _)| guests_count >= *bound).unwrap();
+            bucket.1 += 1;
+        }
+        buckets
+    }
+
+    // Leaderboard of events by number of guests, largest first. Keeps a min-heap of the best
+    // `limit` (at most 20) events while scanning all of them, so it's an O(events) view.
+    pub fn get_top_events_by_attendance(&self, limit: u64) -> Vec<(EventOwnerId, EventJSON, u64)> {
+        let limit = limit.min(MAX_TOP_EVENTS) as usize;
+        let mut top: BinaryHeap<Reverse<(u64, EventOwnerId)>> = BinaryHeap::with_capacity(limit + 1);
+        for (event_owner_id, event) in self.events.iter().filter(|(_, event)| event.is_listed()) {
+            top.push(Reverse((event.guests.len(), event_owner_id)));
+            if top.len() > limit {
+                top.pop();
+            }
+        }
+
+        top.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((guests_count, event_owner_id))| {
+                let event = self.internal_get_event(&event_owner_id);
+                (event_owner_id, event.to_json(self), guests_count)
+            })
+            .collect()
+    }
+
+    // Events that still have no guests, e.g. drafts the organizer forgot about. Fully public view:
+    // views have no caller, so it can't be limited to the admin or the organizer. Every account owns
+    // at most one event, so `organizer` narrows the result down to that account's event.
+    pub fn get_events_without_guests(&self, organizer: Option<AccountId>, from_index: u64, limit: u64) -> Vec<(EventOwnerId, EventJSON)> {
+        self.events.iter()
+            .filter(|(event_owner_id, event)| {
+                event.is_listed() && event.guests.is_empty() && organizer.as_ref().is_none_or(|organizer| organizer == event_owner_id)
+            })
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(event_owner_id, event)| {
+                let event_json = event.to_json(self);
+                (event_owner_id, event_json)
+            })
+            .collect()
+    }
+
+    // Events ordered by price for a sortable table. The map isn't sorted, so every call loads the
+    // first 1000 listed events and sorts them in memory, O(n log n) gas for a page of any size. Events
+    // beyond that bound are not listed. Equal prices keep the map order.
+    pub fn events_by_price_sorted(&self, ascending: bool, from_index: u64, limit: u64) -> Vec<(EventOwnerId, EventJSON)> {
+        let mut events: Vec<Event> = self.events.values().filter(|event| event.is_listed()).take(MAX_PRICE_SORT_SCAN).collect();
+        if ascending {
+            events.sort_by_key(|event| event.price);
+        } else {
+            events.sort_by_key(|event| Reverse(event.price));
+        }
+        events.iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|event| (event.owner_id.clone(), event.to_json(self)))
+            .collect()
+    }
+}
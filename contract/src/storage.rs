@@ -0,0 +1,21 @@
+use crate::*;
+use near_sdk::{Promise, StorageUsage};
+
+// Methods that grow the contract state must be paid for by the caller. They record
+// `env::storage_usage()` before writing and call `refund_deposit` at the end: the attached
+// deposit has to cover the added bytes and whatever is left is sent back.
+pub(crate) fn refund_deposit(initial_storage_usage: StorageUsage) {
+    let used_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+    let required_deposit = Balance::from(used_bytes) * env::storage_byte_cost();
+    let attached_deposit = env::attached_deposit();
+    assert!(
+        required_deposit <= attached_deposit,
+        "ERR_INSUFFICIENT_STORAGE_DEPOSIT: attach at least {} yoctoNEAR",
+        required_deposit
+    );
+
+    let refund = attached_deposit - required_deposit;
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
@@ -0,0 +1,153 @@
+// JSON snapshots of every public view, so changes to the response shape can't slip through
+// unnoticed. Each response is compared with its fixture in `src/tests/snapshots`. After an
+// intended change, regenerate the fixtures with `UPDATE_SNAPSHOTS=1 cargo test` and commit them
+// together with the change.
+use super::*;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json;
+use std::path::PathBuf;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/snapshots").join(format!("{}.json", name))
+}
+
+// returns a readable diff if the response doesn't match its fixture
+fn check_snapshot<T: Serialize>(name: &str, value: &T) -> Option<String> {
+    let actual = serde_json::to_string_pretty(value).unwrap() + "\n";
+    let path = fixture_path(name);
+    if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+        std::fs::write(&path, &actual).unwrap();
+        return None;
+    }
+
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(_) => return Some(format!("{}: missing fixture {}", name, path.display())),
+    };
+    if expected == actual {
+        return None;
+    }
+
+    let (expected_lines, actual_lines): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+    let mut diff = format!("{}: response differs from {}\n", name, path.display());
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        match (expected_lines.get(i), actual_lines.get(i)) {
+            (Some(expected), Some(actual)) if expected == actual => diff.push_str(&format!("  {}\n", expected)),
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    diff.push_str(&format!("- {}\n", expected));
+                }
+                if let Some(actual) = actual {
+                    diff.push_str(&format!("+ {}\n", actual));
+                }
+            }
+        }
+    }
+    Some(diff)
+}
+
+// a contract with every subsystem in use
+fn populated_contract() -> Contract {
+    let mut contract = Contract::default();
+    let (alice, bob, carol) = (account("alice.testnet"), account("bob.testnet"), account("carol.testnet"));
+
+    set_context(&alice, 1_000_000_000);
+    contract.insert_event(event_json(1_500_000_000_000_000_000_000_000, vec!(bob.clone(), carol)));
+    contract.set_slug("alice-party".to_string());
+    contract.set_extension("app.discord".to_string(), "general".to_string());
+    contract.add_event_milestone("book the venue".to_string(), U64::from(2_000_000_000));
+    contract.set_event_localization("es".to_string(), EventLocalization {
+        title: "Fiesta".to_string(),
+        description: "Una fiesta".to_string(),
+    });
+    contract.set_contact_salt(Base64VecU8::from(vec!(1, 2, 3)));
+    contract.create_one_time_invites(vec!(Base64VecU8::from(env::sha256(b"one")), Base64VecU8::from(env::sha256(b"two"))));
+    contract.create_promo_code("VIP".to_string(), None, 3);
+    contract.register_webhook(WebhookRegistration {
+        url: "https://example.com/hook".to_string(),
+        secret: "secret".to_string(),
+        events: vec!(WebhookEventType::GuestAdded),
+        active: true,
+    });
+    contract.draw_winners(alice.clone(), 1);
+    contract.set_signup_form(vec!(
+        SignupField { label: "name".to_string(), field_type: FieldType::Text, required: true },
+        SignupField {
+            label: "diet".to_string(),
+            field_type: FieldType::SelectOne(vec!("vegan".to_string(), "any".to_string())),
+            required: false,
+        },
+    ));
+
+    contract.reserve_block(alice.clone(), account("sponsor.testnet"), 50);
+    contract.set_description_chunk(0, "# Agenda".to_string());
+    contract.finalize_description(1, Base64VecU8::from(env::sha256(b"# Agenda")));
+
+    // every writer gets its own timestamp, so the modification feed has real entries
+    set_context(&account("dave.testnet"), 2_000_000_000);
+    contract.submit_signup_form(alice.clone(), vec!(("name".to_string(), "Dave".to_string())));
+
+    set_context(&bob, 3_000_000_000);
+    contract.register_contact_hash(alice.clone(), Base64VecU8::from(vec!(7; 32)));
+    contract.bookmark_event(alice.clone());
+    contract.insert_event(event_json(10, vec!()));
+
+    set_context(&env::current_account_id(), 4_000_000_000);
+    contract.verify_organizer(alice);
+    contract.grant_method_key("ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap(),
+        vec!("set_guests".to_string()), U128::from(STORAGE_DEPOSIT));
+    contract
+}
+
+#[test]
+fn test_view_snapshots() {
+    let mut contract = populated_contract();
+    let (alice, bob) = (account("alice.testnet"), account("bob.testnet"));
+    set_context(&alice, 1_000_000_000);
+
+    // the build metadata changes with every commit
+    let mut code_info = serde_json::to_value(contract.get_code_info()).unwrap();
+    code_info["commit"] = "<commit>".into();
+    code_info["rustc"] = "<rustc>".into();
+    code_info["dirty"] = "<dirty>".into();
+
+    let failures: Vec<String> = vec!(
+        check_snapshot("get_event", &contract.get_event(alice.clone())),
+        check_snapshot("get_event_without_guests", &contract.get_event(bob.clone())),
+        check_snapshot("get_event_by_slug", &contract.get_event_by_slug("alice-party".to_string())),
+        check_snapshot("get_event_with_permissions", &contract.get_event_with_permissions(alice.clone(), bob.clone())),
+        check_snapshot("get_events_modified_after", &contract.get_events_modified_after(U64::from(0), 10)),
+        check_snapshot("get_events_without_guests", &contract.get_events_without_guests(None, 0, 10)),
+        check_snapshot("events_by_price_sorted", &contract.events_by_price_sorted(true, 0, 10)),
+        check_snapshot("get_events_with_active_promotions", &contract.get_events_with_active_promotions(0, 10)),
+        check_snapshot("get_top_events_by_attendance", &contract.get_top_events_by_attendance(10)),
+        check_snapshot("guest_count_buckets", &contract.guest_count_buckets(0, 10)),
+        check_snapshot("total_unique_guests", &contract.total_unique_guests()),
+        check_snapshot("diff_guests", &contract.diff_guests(alice.clone(), vec!(bob.clone(), account("dave.testnet")))),
+        check_snapshot("registration_log", &contract.registration_log(alice.clone(), 0, 10)),
+        check_snapshot("get_guest_provenance", &contract.get_guest_provenance(alice.clone(), bob.clone())),
+        check_snapshot("ticket_number", &contract.ticket_number(alice.clone(), bob.clone())),
+        check_snapshot("get_raffle_winners", &contract.get_raffle_winners(alice.clone())),
+        check_snapshot("pending_invites_count", &contract.pending_invites_count(alice.clone())),
+        check_snapshot("get_extension", &contract.get_extension(alice.clone(), "app.discord".to_string(), None)),
+        check_snapshot("get_event_localized", &contract.get_event_localized(alice.clone(), "es".to_string(), None)),
+        check_snapshot("get_available_languages", &contract.get_available_languages(alice.clone())),
+        check_snapshot("get_contact_salt", &contract.get_contact_salt(alice.clone())),
+        check_snapshot("export_contact_hashes", &contract.export_contact_hashes(alice.clone(), 0, 10)),
+        check_snapshot("get_events_with_upcoming_milestones", &contract.get_events_with_upcoming_milestones(alice.clone(), 3600)),
+        check_snapshot("get_webhooks", &contract.get_webhooks(alice.clone())),
+        check_snapshot("reserved_remaining", &contract.reserved_remaining(alice.clone(), account("sponsor.testnet"))),
+        check_snapshot("get_description", &contract.get_description(alice.clone(), 0, None)),
+        check_snapshot("get_signup_form", &contract.get_signup_form(alice.clone())),
+        check_snapshot("get_signup_response", &contract.get_signup_response(alice.clone(), account("dave.testnet"))),
+        check_snapshot("get_bookmarks", &contract.get_bookmarks(bob, 0, 10)),
+        check_snapshot("is_organizer_verified", &contract.is_organizer_verified(alice)),
+        check_snapshot("is_analytics_enabled", &contract.is_analytics_enabled()),
+        check_snapshot("get_key_grants", &contract.get_key_grants(0, 10)),
+        check_snapshot("get_network_contribution", &contract.get_network_contribution()),
+        check_snapshot("get_code_info", &code_info),
+        check_snapshot("state_version", &contract.state_version()),
+    ).into_iter().flatten().collect();
+
+    assert!(failures.is_empty(), "{}\nRun `UPDATE_SNAPSHOTS=1 cargo test` if the change is intended.", failures.join("\n"));
+}
@@ -0,0 +1,60 @@
+use crate::*;
+use crate::permissions::ViewerRole;
+
+// Who can find an event. Unlisted events are left out of every listing and discovery view but can
+// still be read by owner id or slug, and stay in the `get_events_modified_after` sync feed. Private
+// events are additionally reduced to a stub in direct reads, except in `get_event_with_permissions`
+// for the owner and the guests. Their localized titles, extensions and description are only
+// returned when the owner or a guest is passed as `viewer`.
+//
+// Private is NOT access control. Views have no authenticated caller, `viewer` is whatever the
+// caller passes, and the owner's account id is the event key, so anyone can pass
+// `viewer = event_owner_id` and read every private field. Besides, all contract state can be read
+// from raw storage. Private only keeps the event out of honest frontends, don't store anything in
+// it that must stay secret.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    Public,
+    Unlisted,
+    Private,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_visibility(&mut self, visibility: Visibility) {
+        let event_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_get_event(&event_owner_id);
+        event.visibility = visibility;
+        self.internal_set_event(&event_owner_id, &mut event);
+    }
+}
+
+impl Event {
+    // included in listing and discovery views
+    pub fn is_listed(&self) -> bool {
+        self.visibility == Visibility::Public
+    }
+
+    // views have no caller, so the frontend passes the signed-in account as `viewer`, unverified
+    pub fn is_visible_to(&self, viewer: Option<&AccountId>) -> bool {
+        self.visibility != Visibility::Private
+            || viewer.is_some_and(|viewer| self.viewer_role(viewer) != ViewerRole::None)
+    }
+
+    // EventJSON for direct reads, a stub without the price, guests and extensions for private events
+    pub fn to_visible_json(&self, contract: &Contract) -> EventJSON {
+        let event_json = self.to_json(contract);
+        if self.visibility != Visibility::Private {
+            return event_json;
+        }
+        EventJSON {
+            price: U128::from(0),
+            guests: vec![],
+            extensions: Default::default(),
+            price_display: String::new(),
+            ..event_json
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::*;
+
+// Organizers register webhook endpoints; the contract can't call them, so it emits a
+// `webhook_trigger` log that an off-chain relay forwards to the url. The secret is public
+// on-chain like all state, so anyone, the relay included, can recompute `hmac_hint`: it only
+// catches accidental corruption of the payload, not tampering. `get_webhooks` leaves the secret
+// out, but it can still be read from the registration call or raw storage.
+const MAX_WEBHOOKS: usize = 5;
+const MAX_WEBHOOK_URL_LENGTH: usize = 256;
+const HMAC_HINT_LENGTH: usize = 8;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventType {
+    EventCreated,
+    GuestAdded,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WebhookRegistration {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEventType>,
+    pub active: bool,
+}
+
+// WebhookRegistration without the secret
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WebhookJSON {
+    pub url: String,
+    pub events: Vec<WebhookEventType>,
+    pub active: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct WebhookTriggerLog {
+    url: String,
+    payload_json: String,
+    hmac_hint: String,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn register_webhook(&mut self, registration: WebhookRegistration) {
+        let initial_storage_usage = env::storage_usage();
+        let organizer = env::predecessor_account_id();
+        assert!(
+            !registration.url.is_empty() && registration.url.len() <= MAX_WEBHOOK_URL_LENGTH,
+            "ERR_INVALID_WEBHOOK_URL"
+        );
+        assert!(!registration.events.is_empty(), "ERR_NO_WEBHOOK_EVENTS");
+
+        let mut webhooks = self.webhooks.get(&organizer).unwrap_or_default();
+        assert!(webhooks.iter().all(|webhook| webhook.url != registration.url), "ERR_WEBHOOK_EXISTS");
+        assert!(webhooks.len() < MAX_WEBHOOKS, "ERR_TOO_MANY_WEBHOOKS");
+        webhooks.push(registration);
+        self.webhooks.insert(&organizer, &webhooks);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn deregister_webhook(&mut self, url: String) {
+        let organizer = env::predecessor_account_id();
+        let mut webhooks = self.webhooks.get(&organizer).expect("ERR_MISSING_WEBHOOK");
+        let index = webhooks.iter().position(|webhook| webhook.url == url).expect("ERR_MISSING_WEBHOOK");
+        webhooks.remove(index);
+
+        if webhooks.is_empty() {
+            self.webhooks.remove(&organizer);
+        } else {
+            self.webhooks.insert(&organizer, &webhooks);
+        }
+    }
+
+    pub fn get_webhooks(&self, organizer: AccountId) -> Vec<WebhookJSON> {
+        self.webhooks
+            .get(&organizer)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|webhook| WebhookJSON { url: webhook.url, events: webhook.events, active: webhook.active })
+            .collect()
+    }
+
+    // log a trigger for every active webhook of the organizer subscribed to `event_type`
+    pub(crate) fn internal_trigger_webhooks<T: Serialize>(
+        &self,
+        organizer: &AccountId,
+        event_type: WebhookEventType,
+        payload: T,
+    ) {
+        let webhooks = match self.webhooks.get(organizer) {
+            Some(webhooks) => webhooks,
+            None => return,
+        };
+        let payload_json = near_sdk::serde_json::to_string(&payload).unwrap();
+        for webhook in webhooks.into_iter().filter(|webhook| webhook.active && webhook.events.contains(&event_type)) {
+            let hmac_hint = hmac_hint(&webhook.secret, &payload_json);
+            emit_event("webhook_trigger", WebhookTriggerLog {
+                url: webhook.url,
+                payload_json: payload_json.clone(),
+                hmac_hint,
+            });
+        }
+    }
+}
+
+// hex of the first 8 bytes of sha256(secret + payload)
+pub(crate) fn hmac_hint(secret: &str, payload_json: &str) -> String {
+    let hash = env::sha256(format!("{}{}", secret, payload_json).as_bytes());
+    hash[..HMAC_HINT_LENGTH].iter().map(|byte| format!("{:02x}", byte)).collect()
+}